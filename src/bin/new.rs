@@ -1,32 +1,165 @@
 use anyhow::bail;
 use ntek::Serialize;
+use parking_lot::Mutex;
 use tsck_kee::{Event, Kee, TKeePair};
 use tsck_window::overlay::{
+    backend::Win32Backend,
     config::{CycleDirection, NtekConfig, SomeFunc},
-    manager::OverlayManager,
+    config_check::{self, Severity},
+    config_file::{self, ConfigSource},
+    ipc::{
+        self, client,
+        protocol::{Query, Reply, Request},
+    },
+    manager::{self, OverlayManager, Shared},
+    rescue, win_api,
 };
 
-use std::{io::Write, sync::Arc, thread};
+use std::{io::Write, path::PathBuf, process, sync::Arc, thread};
 
 fn main() -> anyhow::Result<()> {
-    let conf_file = include_str!("../../config.ntek");
-    let config = Arc::new(ntek::from_str::<NtekConfig>(conf_file).expect("Failed to parse config"));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("check-config") {
+        let Some(path) = args.get(1) else {
+            bail!("usage: tsck-window check-config <file>");
+        };
+        process::exit(check_config(path));
+    }
+    if args.first().map(String::as_str) == Some("rescue") {
+        let moved = rescue::rescue_windows(&Win32Backend);
+        println!("moved {} off screen windows back: {moved:?}", moved.len());
+        return Ok(());
+    }
+    if let Some(i) = args.iter().position(|a| a == "--dump-state") {
+        let path = args.get(i + 1).filter(|a| !a.starts_with("--"));
+        process::exit(dump_state(path));
+    }
+    let cli_config = config_file::config_path_from_args(args);
+    let (config, source) = config_file::load_config(cli_config)?;
+    println!("config: {source}");
+    let config = Arc::new(config);
     let manager = Arc::new(OverlayManager::new(config.clone()));
+    install_shutdown_hooks(manager.clone());
     spawn_command_interface(manager.clone());
-    spawn_hotkey(manager.clone(), config.clone());
+    let hotkeys = Hotkeys {
+        manager: manager.clone(),
+        config: Arc::new(Mutex::new(config)),
+        listener: Arc::new(Mutex::new(None)),
+    };
+    hotkeys.register();
+    spawn_ipc_server(source.clone(), hotkeys.clone());
+    spawn_config_watcher(source, hotkeys);
     loop {
         thread::park();
     }
 }
 
+/// Ctrl+C and panics on an event loop thread put parked windows back before
+/// the process goes away, other threads that panic are only logged
+fn install_shutdown_hooks(manager: Arc<OverlayManager>) {
+    let on_ctrl_c = manager.clone();
+    if let Err(err) = ctrlc::set_handler(move || {
+        on_ctrl_c.shutdown();
+        process::exit(0);
+    }) {
+        eprintln!("Error setting Ctrl+C handler {err}");
+    }
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let name = thread::current().name().unwrap_or("<unnamed>").to_string();
+        if !manager::EVENT_LOOP_THREADS.contains(&name.as_str()) {
+            eprintln!("thread `{name}` panicked, carrying on without it");
+            return;
+        }
+        // the panicking thread may hold the handler, it lets go while unwinding
+        let manager = manager.clone();
+        thread::spawn(move || {
+            manager.shutdown();
+            process::exit(101);
+        });
+    }));
+}
+
+/// ask the running manager for everything it knows, into `path` or stdout
+fn dump_state(path: Option<&String>) -> i32 {
+    let name = ipc::socket_name_from_env();
+    let state = match client::request(&name, &Request::Query(Query::State)) {
+        Ok(Reply {
+            data: Some(state), ..
+        }) => state,
+        Ok(reply) => {
+            eprintln!("{}", reply.error.unwrap_or_default());
+            return 1;
+        }
+        Err(err) => {
+            eprintln!("cant reach tsck-window on {name}: {err}");
+            return 1;
+        }
+    };
+    let json = serde_json::to_string_pretty(&state).unwrap_or_default();
+    let Some(path) = path else {
+        println!("{json}");
+        return 0;
+    };
+    match std::fs::write(path, json) {
+        Ok(()) => {
+            println!("state: {path}");
+            0
+        }
+        Err(err) => {
+            eprintln!("cant write {path}: {err}");
+            1
+        }
+    }
+}
+
+/// print every problem in `path`, non zero exit code when one of them is an error
+fn check_config(path: &str) -> i32 {
+    let source = ConfigSource::Cli(PathBuf::from(path));
+    match config_file::check(&source) {
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+        Ok((_, diagnostics)) => {
+            for diagnostic in diagnostics.iter() {
+                println!("{}", config_file::format_diagnostic(&source, diagnostic));
+            }
+            let errors = diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Error)
+                .count();
+            println!(
+                "{source}: {errors} error(s), {} warning(s)",
+                diagnostics.len() - errors
+            );
+            if config_check::has_errors(&diagnostics) {
+                1
+            } else {
+                0
+            }
+        }
+    }
+}
+
 fn help_command_interface() {
     println!(
         r#"
 ws prev
 ws next
+ws back
+ws <name | index>
+ws new <name>
+ws rename <name>
+ws delete
+ws left
+ws right
 ws reset
 ws list
 list
+state
+quit
 app move up
 app move down
 app move left
@@ -36,26 +169,111 @@ app resize
     "#
     );
 }
-fn spawn_hotkey(manager: Arc<OverlayManager>, config: Arc<NtekConfig>) {
-    let mut kee = Kee::new(false);
-    let kees = config
-        .hotkeys
-        .iter()
-        .map(|(k, f)| TKeePair::new(k.to_string(), f.serialize()))
-        .collect::<Vec<_>>();
-    kee.on_message(move |event| match event {
-        Event::Keys(k, _) => {
-            if let Some(somefunc) = config.hotkeys.get(k) {
-                match somefunc {
-                    SomeFunc::W(ws_func) => {
-                        ws_func.do_stuff(manager.clone(), config.clone());
+/// the thread running the `Kee` and the keys it listens for
+struct Listener {
+    thread_id: u32,
+    keys: Vec<String>,
+}
+
+/// one `Kee` at a time, it looks up what a key does in the current config.
+/// only a change to the set of keys replaces it
+#[derive(Clone)]
+struct Hotkeys {
+    manager: Arc<OverlayManager>,
+    config: Shared<Arc<NtekConfig>>,
+    listener: Shared<Option<Listener>>,
+}
+impl Hotkeys {
+    fn register(&self) {
+        let config = self.config.lock().clone();
+        let mut keys = config.hotkeys.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        let mut listener = self.listener.lock();
+        if let Some(running) = listener.as_ref() {
+            if running.keys == keys {
+                return;
+            }
+            // its message loop ends and the keyboard hook is removed with the thread
+            win_api::quit_thread(running.thread_id);
+        }
+        let kees = config
+            .hotkeys
+            .iter()
+            .map(|(k, f)| TKeePair::new(k.to_string(), f.serialize()))
+            .collect::<Vec<_>>();
+        let hotkeys = self.clone();
+        let (started, thread_id) = flume::bounded(1);
+        let spawned = thread::Builder::new()
+            .name("hotkeys".into())
+            .spawn(move || {
+                _ = started.send(win_api::current_thread_id());
+                let mut kee = Kee::new(false);
+                kee.on_message(move |event| match event {
+                    Event::Keys(k, _) => hotkeys.run(k),
+                    _ => {}
+                })
+                .run(kees);
+            });
+        if let Err(err) = spawned {
+            eprintln!("Error starting hotkeys: {err}");
+            *listener = None;
+            return;
+        }
+        *listener = thread_id
+            .recv()
+            .ok()
+            .map(|thread_id| Listener { thread_id, keys });
+    }
+    fn run(&self, key: &str) {
+        let config = self.config.lock().clone();
+        if let Some(somefunc) = config.hotkeys.get(key) {
+            match somefunc {
+                SomeFunc::W(ws_func) => {
+                    if let Err(err) = ws_func.do_stuff(self.manager.clone(), config.clone()) {
+                        eprintln!("Error {key}: {err:#}");
                     }
                 }
             }
         }
-        _ => {}
-    })
-    .run(kees);
+    }
+    fn reload(&self, config: NtekConfig) {
+        self.manager.reload(&config);
+        *self.config.lock() = Arc::new(config);
+        self.register();
+    }
+}
+/// scripts drive the manager through the same `WF` commands as the hotkeys
+fn spawn_ipc_server(source: ConfigSource, hotkeys: Hotkeys) {
+    let name = ipc::socket_name_from_env();
+    let server = ipc::server::spawn(&name, move |request| match request {
+        Request::Reload => match config_file::load(&source) {
+            Ok(config) => {
+                hotkeys.reload(config);
+                Reply::ok()
+            }
+            Err(err) => {
+                hotkeys.manager.show_config_error(&err);
+                Reply::error(err)
+            }
+        },
+        request => {
+            let config = hotkeys.config.lock().clone();
+            ipc::dispatch(&hotkeys.manager, config, request)
+        }
+    });
+    match server {
+        Ok(()) => println!("ipc: {name}"),
+        Err(err) => eprintln!("Error starting IPC server on {name}: {err}"),
+    }
+}
+fn spawn_config_watcher(source: ConfigSource, hotkeys: Hotkeys) {
+    config_file::watch(source, move |result| match result {
+        Ok(config) => {
+            hotkeys.reload(config);
+            println!("config reloaded");
+        }
+        Err(err) => hotkeys.manager.show_config_error(&err),
+    });
 }
 fn spawn_command_interface(manager: Arc<OverlayManager>) {
     let manager = manager.clone();
@@ -67,14 +285,30 @@ fn spawn_command_interface(manager: Arc<OverlayManager>) {
             match input.trim() {
                 c if c.starts_with("ws") => {
                     if let Some((_, s)) = c.split_once(' ') {
-                        let direction = match s {
-                            "prev" => CycleDirection::Prev,
-                            "next" => CycleDirection::Next,
-                            _ => CycleDirection::Next,
-                        };
-                        manager.with_handler(|handler| {
-                            handler.go_to_workspace(&direction);
+                        let result = manager.with_handler(|handler| match s.split_once(' ') {
+                            Some(("new", name)) => handler
+                                .create_workspace(name, handler.get_active_monitor())
+                                .map(drop),
+                            Some(("rename", name)) => handler.rename_active_workspace(name),
+                            _ if s == "delete" => handler.delete_active_workspace(),
+                            _ => {
+                                match s {
+                                    "prev" => handler.go_to_workspace(&CycleDirection::Prev),
+                                    "next" => handler.go_to_workspace(&CycleDirection::Next),
+                                    "back" => handler.go_to_previous_workspace(),
+                                    "left" => handler.move_active_workspace(&CycleDirection::Prev),
+                                    "right" => handler.move_active_workspace(&CycleDirection::Next),
+                                    name => match handler.find_workspace(name) {
+                                        Some(index) => handler.show_workspace(index),
+                                        None => bail!("no workspace `{name}`"),
+                                    },
+                                };
+                                Ok(())
+                            }
                         });
+                        if let Err(err) = result {
+                            eprintln!("Error: {err:#}");
+                        }
                     }
                 }
                 "order" => manager.with_handler(|h| {
@@ -86,9 +320,17 @@ fn spawn_command_interface(manager: Arc<OverlayManager>) {
 
                 "list" => {
                     manager.with_handler(|handler| {
-                        handler.apps.iter().for_each(|(_, ai)| {
-                            println!("{:<10} {:?} {:?}", ai.exe, ai.position, ai.size);
-                        });
+                        print!("{}", handler.snapshot());
+                    });
+                }
+                "quit" => {
+                    manager.shutdown();
+                    process::exit(0);
+                }
+                "state" => {
+                    manager.with_handler(|handler| {
+                        let json = serde_json::to_string_pretty(&handler.snapshot());
+                        println!("{}", json.unwrap_or_default());
                     });
                 }
                 _ => {
@@ -4,6 +4,7 @@ use std::{collections::HashMap, io::Write, process, sync::Arc};
 use tsck_kee::{Event, Kee, TKeePair};
 use tsck_window::{
     hook::{ArcMutWHookHandler, SlotText, SystemInfo, WindowHook, format_speed},
+    overlay::config_file,
     slot_text, with_handler,
 };
 
@@ -63,6 +64,8 @@ struct NtekConfig {
     hotkeys: HashMap<String, SomeFunc>,
     blacklist: Vec<String>,
     size_factor: Vec<f32>,
+    outer_gap: Option<i32>,
+    inner_gap: Option<i32>,
 }
 
 impl WF {
@@ -271,13 +274,18 @@ fn spawn_widget(handler: ArcMutWHookHandler) {
 }
 
 fn main() -> anyhow::Result<()> {
-    let ntek_config = ntek::from_str::<NtekConfig>(include_str!("../../config.ntek"))
-        .expect("failed reading shirt");
+    let source =
+        config_file::discover(config_file::config_path_from_args(std::env::args().skip(1)));
+    let ntek_config = config_file::load_with(&source, ntek::from_str::<NtekConfig>)?;
     WindowHook::new(
         ntek_config.blacklist.clone(),
         ntek_config.workspaces.clone(),
         ntek_config.size_factor.clone(),
     )
+    .with_gaps(
+        ntek_config.outer_gap.unwrap_or(0),
+        ntek_config.inner_gap.unwrap_or(0),
+    )
     .bind(|handler| {
         spawn_widget(handler.clone());
         spawn_hotkee(handler, ntek_config);
@@ -0,0 +1,80 @@
+use std::{
+    env,
+    io::{self, Write},
+    process,
+};
+use tsck_window::overlay::{
+    events::EventKind,
+    ipc::{
+        self,
+        cli::{self, Invocation},
+        client,
+        protocol::{Query, Reply, Request},
+    },
+};
+
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_NOT_RUNNING: i32 = 3;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let cli = match cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(message) => {
+            eprintln!("{message}");
+            process::exit(EXIT_USAGE);
+        }
+    };
+    let name = ipc::socket_name_from_env();
+    let query = match &cli.invocation {
+        Invocation::Send(Request::Query(query)) => Some(*query),
+        _ => None,
+    };
+    let reply = match cli.invocation {
+        Invocation::Send(request) => client::request(&name, &request),
+        Invocation::Subscribe(kinds) => process::exit(print_events(&name, kinds)),
+    };
+    let reply = match reply {
+        Ok(reply) => reply,
+        Err(err) => {
+            eprintln!("cant reach tsck-window on {name}: {err}");
+            process::exit(EXIT_NOT_RUNNING);
+        }
+    };
+    process::exit(print_reply(&reply, query, cli.json));
+}
+
+/// events are JSON lines either way, runs until the manager goes away
+fn print_events(name: &str, kinds: Vec<EventKind>) -> i32 {
+    let lines = match client::subscribe(name, kinds) {
+        Ok(lines) => lines,
+        Err(err) => {
+            eprintln!("cant reach tsck-window on {name}: {err}");
+            return EXIT_NOT_RUNNING;
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    for line in lines.map_while(Result::ok) {
+        // stdout going away (`| head`) ends the subscription
+        if writeln!(stdout, "{line}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+    0
+}
+
+/// `--json` prints the reply line as the manager sent it
+fn print_reply(reply: &Reply, query: Option<Query>, json: bool) -> i32 {
+    if json {
+        println!("{}", serde_json::to_string(reply).unwrap_or_default());
+    } else if let Some(err) = &reply.error {
+        eprintln!("error: {err}");
+    } else if let (Some(query), Some(data)) = (query, &reply.data) {
+        println!("{}", cli::format_query(query, data));
+    }
+    if reply.ok { 0 } else { EXIT_ERROR }
+}
@@ -8,7 +8,9 @@ use crate::{
         win_api::{self, BORDER_MANAGER, MonitorInfo},
         win_event::WinEvent,
     },
-    hwnd, slot_text,
+    hwnd,
+    overlay::layout::{Gaps, LayoutRect},
+    slot_text,
 };
 
 use parking_lot::Mutex;
@@ -70,6 +72,7 @@ pub struct WindowHookHandler {
     workspaces: Vec<Workspace>,
     border_hwnds: Vec<isize>,
     size_factor: Vec<f32>,
+    gaps: Gaps,
     statusbar_left: Vec<SlotText>,
     statusbar_center: Vec<SlotText>,
     statusbar_right: Vec<SlotText>,
@@ -89,6 +92,7 @@ impl WindowHookHandler {
             active_app_index: 0,
             border_hwnds: Vec::new(),
             size_factor,
+            gaps: Gaps::default(),
             statusbar_left: vec![],
             statusbar_center: vec![],
             statusbar_right: vec![],
@@ -619,22 +623,27 @@ impl WindowHookHandler {
         Some(())
     }
 
+    /// monitor rect with the outer gap taken off
+    fn gapped_monitor(&self, moni_left: i32, moni_w: i32, moni_h: i32) -> LayoutRect {
+        self.gaps
+            .shrink(LayoutRect::new(moni_left, 0, moni_w, moni_h))
+    }
     fn go_animate(&mut self) -> Option<()> {
         let (moni_left, moni_w, moni_h, active_hwnd, pos, size, px, py, _ratio, column) =
             self.get_app_props()?;
-        let width = (self.size_factor[self.width_selector_index] * moni_w as f32) as i32;
-        let height = (self.size_factor[self.height_selector_index] * moni_h as f32) as i32;
-        let w = width + px;
-        let h = height + py;
-        let to_pos = match column {
-            Column::Left => AppPosition {
-                x: moni_left + (-(px / 2)),
-                y: 0,
-            },
-            Column::Right => AppPosition {
-                x: moni_left + ((moni_w - (px / 2)) - width),
-                y: 0,
-            },
+        let area = self.gapped_monitor(moni_left, moni_w, moni_h);
+        let width = (self.size_factor[self.width_selector_index] * area.width as f32) as i32;
+        let height = (self.size_factor[self.height_selector_index] * area.height as f32) as i32;
+        let cell = match column {
+            Column::Left => LayoutRect::new(area.x, area.y, width, height),
+            Column::Right => LayoutRect::new(area.right() - width, area.y, width, height),
+        };
+        let cell = self.gaps.inset(cell, area);
+        let w = cell.width + px;
+        let h = cell.height + py;
+        let to_pos = AppPosition {
+            x: cell.x + (-(px / 2)),
+            y: cell.y,
         };
         animation::animate_window(
             active_hwnd,
@@ -649,14 +658,15 @@ impl WindowHookHandler {
     pub fn fake_maximize(&mut self) -> Option<()> {
         let (moni_left, moni_w, moni_h, active_hwnd, pos, size, px, py, _ratio, column) =
             self.get_app_props()?;
-        let width = (self.size_factor[self.width_selector_index] * moni_w as f32) as i32;
-        let height = (self.size_factor[self.height_selector_index] * moni_h as f32) as i32;
+        let area = self.gapped_monitor(moni_left, moni_w, moni_h);
+        let width = (self.size_factor[self.width_selector_index] * area.width as f32) as i32;
+        let height = (self.size_factor[self.height_selector_index] * area.height as f32) as i32;
         let toolbar_height = win_api::get_toolbar_height(self.monitor_index_for(active_hwnd));
 
         let w = width + px;
         let h = height + (py / 2);
-        let x = moni_left + (-(px / 2));
-        let y = toolbar_height;
+        let x = area.x + (-(px / 2));
+        let y = toolbar_height + self.gaps.outer;
         win_api::set_app_size_position(hwnd!(active_hwnd), x, y, w, h, true);
         Some(())
     }
@@ -666,11 +676,21 @@ impl WindowHookHandler {
             self.get_app_props()?;
         let toolbar_height = win_api::get_toolbar_height(self.monitor_index_for(active_hwnd));
         self.app_position = (self.app_position + 1) % grid.len();
+        let area = self.gapped_monitor(moni_left, moni_w, moni_h);
         if let Some((x, y, w, h)) = grid.get(self.app_position) {
-            let x = moni_left + ((moni_w as f32 * x) as i32 - (px / 2));
-            let y = (moni_h as f32 * y) as i32 - (py / 2) + toolbar_height;
-            let w = (moni_w as f32 * w) as i32 + px;
-            let h = (moni_h as f32 * h) as i32 + py;
+            let cell = self.gaps.inset(
+                LayoutRect::new(
+                    area.x + (area.width as f32 * x) as i32,
+                    area.y + (area.height as f32 * y) as i32,
+                    (area.width as f32 * w) as i32,
+                    (area.height as f32 * h) as i32,
+                ),
+                area,
+            );
+            let x = cell.x - (px / 2);
+            let y = cell.y - (py / 2) + toolbar_height;
+            let w = cell.width + px;
+            let h = cell.height + py;
             animation::animate_window(
                 active_hwnd,
                 pos,
@@ -925,6 +945,11 @@ impl WindowHook {
         }
     }
 
+    pub fn with_gaps(self, outer: i32, inner: i32) -> Self {
+        self.handler.lock().gaps = Gaps::new(outer, inner);
+        self
+    }
+
     pub fn bind<F>(self, f: F) -> Self
     where
         F: FnOnce(ArcMutWHookHandler),
@@ -1,15 +1,15 @@
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
 pub enum Column {
     Left,
     Right,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
 pub struct SizeRatio {
     pub width: f32,
     pub height: f32,
 }
-#[derive(Debug, Copy, Default, PartialEq, Clone)]
+#[derive(Debug, Copy, Default, PartialEq, Clone, serde::Serialize)]
 pub struct AppPosition {
     pub x: i32,
     pub y: i32,
@@ -25,7 +25,7 @@ impl std::fmt::Display for AppPosition {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
 pub struct AppSize {
     pub width: i32,
     pub height: i32,
@@ -41,7 +41,7 @@ impl std::fmt::Display for AppSize {
         write!(f, "({}, {})", self.width, self.height)
     }
 }
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
 pub struct AppInfo {
     pub hwnd: isize,
     pub exe: String,
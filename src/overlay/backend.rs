@@ -0,0 +1,346 @@
+use crate::{
+    hwnd,
+    overlay::{
+        animation::{self, AnimationEasing},
+        app_info::{AppInfo, AppPosition, AppSize},
+        monitor_info::{self, StatusbarMonitorInfo},
+        win_api,
+        workspaces::Hwnd,
+    },
+};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+//==============================================================================//
+// tag         : WINDOW BACKEND
+// description : everything OverlayHandler needs from the window system,
+//               so the workspace/grid logic can run against a fake one
+//==============================================================================//
+pub trait WindowBackend: Send + Sync {
+    fn list_windows(&self) -> Vec<Hwnd>;
+    fn get_rect(&self, hwnd: Hwnd) -> (AppSize, AppPosition);
+    fn get_rect_padding(&self, hwnd: Hwnd) -> (i32, i32);
+    fn set_position(&self, hwnd: Hwnd, x: i32, y: i32);
+    fn set_size_position(&self, hwnd: Hwnd, x: i32, y: i32, width: i32, height: i32);
+    fn animate_window(
+        &self,
+        hwnd: Hwnd,
+        pos: AppPosition,
+        to_pos: AppPosition,
+        size: AppSize,
+        to_size: AppSize,
+        easing: AnimationEasing,
+    );
+    fn is_maximized(&self, hwnd: Hwnd) -> bool;
+    fn is_minimized(&self, hwnd: Hwnd) -> bool;
+    fn is_top_most(&self, hwnd: Hwnd) -> bool;
+    /// returns the new topmost state, `border` is kept above the app
+    fn toggle_top_most(&self, hwnd: Hwnd, border: Hwnd) -> bool;
+    fn bring_to_front(&self, hwnd: Hwnd, border: Hwnd);
+    fn close(&self, hwnd: Hwnd) -> anyhow::Result<()>;
+    /// starts `command`, a program and its arguments
+    fn launch(&self, command: &str) -> anyhow::Result<()>;
+    fn get_monitors(&self) -> Vec<StatusbarMonitorInfo>;
+    fn get_monitor_index(&self, hwnd: Hwnd, monitors: &[StatusbarMonitorInfo]) -> Option<usize>;
+    fn get_monitor_index_from_cursor(&self, monitors: &[StatusbarMonitorInfo]) -> usize;
+
+    fn animate_position(&self, app: &AppInfo, to_pos: AppPosition, easing: AnimationEasing) {
+        self.animate_window(
+            app.hwnd,
+            app.position,
+            to_pos,
+            app.size.clone(),
+            app.size.clone(),
+            easing,
+        );
+    }
+}
+
+//==============================================================================//
+// tag         : WIN32
+// description : the real thing
+//==============================================================================//
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Win32Backend;
+
+impl WindowBackend for Win32Backend {
+    fn list_windows(&self) -> Vec<Hwnd> {
+        win_api::list_windows()
+    }
+    fn get_rect(&self, hwnd: Hwnd) -> (AppSize, AppPosition) {
+        win_api::get_rect(hwnd!(hwnd))
+    }
+    fn get_rect_padding(&self, hwnd: Hwnd) -> (i32, i32) {
+        win_api::get_rect_padding(hwnd)
+    }
+    fn set_position(&self, hwnd: Hwnd, x: i32, y: i32) {
+        win_api::set_app_position(hwnd!(hwnd), x, y);
+    }
+    fn set_size_position(&self, hwnd: Hwnd, x: i32, y: i32, width: i32, height: i32) {
+        win_api::set_app_size_position(hwnd!(hwnd), x, y, width, height, true);
+    }
+    fn animate_window(
+        &self,
+        hwnd: Hwnd,
+        pos: AppPosition,
+        to_pos: AppPosition,
+        size: AppSize,
+        to_size: AppSize,
+        easing: AnimationEasing,
+    ) {
+        animation::animate_window(hwnd, pos, to_pos, size, to_size, easing);
+    }
+    fn is_maximized(&self, hwnd: Hwnd) -> bool {
+        win_api::is_maximized(hwnd)
+    }
+    fn is_minimized(&self, hwnd: Hwnd) -> bool {
+        win_api::is_minimized(hwnd)
+    }
+    fn is_top_most(&self, hwnd: Hwnd) -> bool {
+        win_api::is_top_most(hwnd!(hwnd))
+    }
+    fn toggle_top_most(&self, hwnd: Hwnd, border: Hwnd) -> bool {
+        win_api::toggle_top_most(hwnd!(hwnd), hwnd!(border))
+    }
+    fn bring_to_front(&self, hwnd: Hwnd, border: Hwnd) {
+        win_api::bring_to_front(hwnd!(hwnd), hwnd!(border));
+    }
+    fn close(&self, hwnd: Hwnd) -> anyhow::Result<()> {
+        win_api::close_app(hwnd!(hwnd))
+    }
+    fn launch(&self, command: &str) -> anyhow::Result<()> {
+        win_api::launch(command)
+    }
+    fn get_monitors(&self) -> Vec<StatusbarMonitorInfo> {
+        monitor_info::get_monitors()
+    }
+    fn get_monitor_index(&self, hwnd: Hwnd, monitors: &[StatusbarMonitorInfo]) -> Option<usize> {
+        win_api::get_monitor_index(hwnd!(hwnd), monitors)
+    }
+    fn get_monitor_index_from_cursor(&self, monitors: &[StatusbarMonitorInfo]) -> usize {
+        win_api::get_monitor_index_from_cursor(monitors)
+    }
+}
+
+//==============================================================================//
+// tag         : FAKE
+// description : in-memory window system, records every call it gets.
+//               animations are applied instantly.
+//==============================================================================//
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendCall {
+    SetPosition {
+        hwnd: Hwnd,
+        x: i32,
+        y: i32,
+    },
+    SetSizePosition {
+        hwnd: Hwnd,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    Animate {
+        hwnd: Hwnd,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    ToggleTopMost(Hwnd),
+    BringToFront(Hwnd),
+    Close(Hwnd),
+    Launch(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FakeWindow {
+    pub position: AppPosition,
+    pub size: AppSize,
+    pub monitor: usize,
+    pub padding: (i32, i32),
+    pub top_most: bool,
+    pub maximized: bool,
+    pub minimized: bool,
+}
+
+#[derive(Default)]
+struct FakeState {
+    windows: BTreeMap<Hwnd, FakeWindow>,
+    monitors: Vec<StatusbarMonitorInfo>,
+    cursor_monitor: usize,
+    calls: Vec<BackendCall>,
+}
+
+#[derive(Default)]
+pub struct FakeBackend {
+    state: Mutex<FakeState>,
+}
+
+impl FakeBackend {
+    pub fn new(monitors: Vec<StatusbarMonitorInfo>) -> Self {
+        Self {
+            state: Mutex::new(FakeState {
+                monitors,
+                ..Default::default()
+            }),
+        }
+    }
+    pub fn add_window(&self, hwnd: Hwnd, position: AppPosition, size: AppSize, monitor: usize) {
+        self.state.lock().windows.insert(
+            hwnd,
+            FakeWindow {
+                position,
+                size,
+                monitor,
+                padding: (0, 0),
+                top_most: false,
+                maximized: false,
+                minimized: false,
+            },
+        );
+    }
+    pub fn set_minimized(&self, hwnd: Hwnd, minimized: bool) {
+        if let Some(window) = self.state.lock().windows.get_mut(&hwnd) {
+            window.minimized = minimized;
+        }
+    }
+    pub fn set_cursor_monitor(&self, monitor: usize) {
+        self.state.lock().cursor_monitor = monitor;
+    }
+    pub fn window(&self, hwnd: Hwnd) -> Option<FakeWindow> {
+        self.state.lock().windows.get(&hwnd).cloned()
+    }
+    pub fn calls(&self) -> Vec<BackendCall> {
+        self.state.lock().calls.clone()
+    }
+    pub fn clear_calls(&self) {
+        self.state.lock().calls.clear();
+    }
+    fn place(&self, hwnd: Hwnd, position: AppPosition, size: Option<AppSize>, call: BackendCall) {
+        let mut state = self.state.lock();
+        let monitor = state.monitors.iter().position(|m| {
+            position.x >= m.x
+                && position.x < m.x + m.width
+                && position.y >= m.y
+                && position.y < m.y + m.height
+        });
+        if let Some(window) = state.windows.get_mut(&hwnd) {
+            window.position = position;
+            if let Some(size) = size {
+                window.size = size;
+            }
+            // parked windows keep the monitor they belong to
+            if let Some(monitor) = monitor {
+                window.monitor = monitor;
+            }
+        }
+        state.calls.push(call);
+    }
+}
+
+impl WindowBackend for FakeBackend {
+    fn list_windows(&self) -> Vec<Hwnd> {
+        self.state.lock().windows.keys().copied().collect()
+    }
+    fn get_rect(&self, hwnd: Hwnd) -> (AppSize, AppPosition) {
+        self.window(hwnd)
+            .map(|w| (w.size, w.position))
+            .unwrap_or((AppSize::new(0, 0), AppPosition::default()))
+    }
+    fn get_rect_padding(&self, hwnd: Hwnd) -> (i32, i32) {
+        self.window(hwnd).map(|w| w.padding).unwrap_or((0, 0))
+    }
+    fn set_position(&self, hwnd: Hwnd, x: i32, y: i32) {
+        self.place(
+            hwnd,
+            AppPosition::new(x, y),
+            None,
+            BackendCall::SetPosition { hwnd, x, y },
+        );
+    }
+    fn set_size_position(&self, hwnd: Hwnd, x: i32, y: i32, width: i32, height: i32) {
+        self.place(
+            hwnd,
+            AppPosition::new(x, y),
+            Some(AppSize::new(width, height)),
+            BackendCall::SetSizePosition {
+                hwnd,
+                x,
+                y,
+                width,
+                height,
+            },
+        );
+    }
+    fn animate_window(
+        &self,
+        hwnd: Hwnd,
+        _pos: AppPosition,
+        to_pos: AppPosition,
+        _size: AppSize,
+        to_size: AppSize,
+        _easing: AnimationEasing,
+    ) {
+        let call = BackendCall::Animate {
+            hwnd,
+            x: to_pos.x,
+            y: to_pos.y,
+            width: to_size.width,
+            height: to_size.height,
+        };
+        self.place(hwnd, to_pos, Some(to_size), call);
+    }
+    fn is_maximized(&self, hwnd: Hwnd) -> bool {
+        self.window(hwnd).is_some_and(|w| w.maximized)
+    }
+    fn is_minimized(&self, hwnd: Hwnd) -> bool {
+        self.window(hwnd).is_some_and(|w| w.minimized)
+    }
+    fn is_top_most(&self, hwnd: Hwnd) -> bool {
+        self.window(hwnd).is_some_and(|w| w.top_most)
+    }
+    fn toggle_top_most(&self, hwnd: Hwnd, _border: Hwnd) -> bool {
+        let mut state = self.state.lock();
+        state.calls.push(BackendCall::ToggleTopMost(hwnd));
+        match state.windows.get_mut(&hwnd) {
+            Some(window) => {
+                window.top_most = !window.top_most;
+                window.top_most
+            }
+            None => false,
+        }
+    }
+    fn bring_to_front(&self, hwnd: Hwnd, _border: Hwnd) {
+        self.state
+            .lock()
+            .calls
+            .push(BackendCall::BringToFront(hwnd));
+    }
+    fn close(&self, hwnd: Hwnd) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        state.calls.push(BackendCall::Close(hwnd));
+        state
+            .windows
+            .remove(&hwnd)
+            .map(|_| ())
+            .ok_or(anyhow::anyhow!("no such window {hwnd}"))
+    }
+    fn launch(&self, command: &str) -> anyhow::Result<()> {
+        self.state
+            .lock()
+            .calls
+            .push(BackendCall::Launch(command.to_string()));
+        Ok(())
+    }
+    fn get_monitors(&self) -> Vec<StatusbarMonitorInfo> {
+        self.state.lock().monitors.clone()
+    }
+    fn get_monitor_index(&self, hwnd: Hwnd, _monitors: &[StatusbarMonitorInfo]) -> Option<usize> {
+        self.window(hwnd).map(|w| w.monitor)
+    }
+    fn get_monitor_index_from_cursor(&self, _monitors: &[StatusbarMonitorInfo]) -> usize {
+        self.state.lock().cursor_monitor
+    }
+}
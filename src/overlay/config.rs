@@ -1,17 +1,26 @@
+use anyhow::Context;
 use ntek::{self, Serialize};
 use ntek_derive::{NtekDes, NtekSer};
 use std::{collections::HashMap, sync::Arc};
 
-use crate::overlay::manager::OverlayManager;
+use crate::overlay::{
+    layout::{
+        GAP_STEP, GapSettings, Gaps,
+        master_stack::{MASTER_RATIO_STEP, MasterStack},
+    },
+    manager::OverlayManager,
+    rules::GridCell,
+    workspaces::Workspace,
+};
 
-#[derive(Debug, NtekDes, NtekSer)]
-enum Direction {
+#[derive(Debug, Clone, Copy, PartialEq, NtekDes, NtekSer, serde::Serialize, serde::Deserialize)]
+pub enum Direction {
     Up,
     Down,
     Left,
     Right,
 }
-#[derive(Debug, NtekDes, NtekSer)]
+#[derive(Debug, NtekDes, NtekSer, serde::Serialize, serde::Deserialize)]
 pub enum CycleDirection {
     Prev,
     Next,
@@ -23,8 +32,44 @@ impl CycleDirection {
             CycleDirection::Next => "Next",
         }
     }
+    /// step `index` within `0..count`, wrapping around
+    pub fn cycle(&self, index: usize, count: usize) -> usize {
+        match self {
+            CycleDirection::Prev => (index + count - 1) % count,
+            CycleDirection::Next => (index + 1) % count,
+        }
+    }
 }
-#[derive(Debug, NtekDes, NtekSer)]
+#[derive(Debug, Clone, Copy, PartialEq, NtekDes, NtekSer, serde::Serialize, serde::Deserialize)]
+pub enum MonitorTarget {
+    Next,
+    Prev,
+    Index(usize),
+}
+impl MonitorTarget {
+    /// target monitor seen from `current`, `None` when it is `current` itself or does not exist
+    pub fn resolve(&self, current: usize, count: usize) -> Option<usize> {
+        let target = match self {
+            MonitorTarget::Next => CycleDirection::Next.cycle(current, count.max(1)),
+            MonitorTarget::Prev => CycleDirection::Prev.cycle(current, count.max(1)),
+            MonitorTarget::Index(index) => *index,
+        };
+        (target != current && target < count).then_some(target)
+    }
+}
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, NtekDes, NtekSer, serde::Serialize, serde::Deserialize,
+)]
+pub enum LayoutKind {
+    #[default]
+    Floating,
+    Bsp,
+    MasterStack,
+    Scrolling,
+    Monocle,
+}
+/// everything a hotkey can do, also the `command` of an IPC request
+#[derive(Debug, NtekDes, NtekSer, serde::Serialize, serde::Deserialize)]
 pub enum WF {
     MoveActiveApp(Direction),
     ResizeActiveApp(Direction),
@@ -32,12 +77,54 @@ pub enum WF {
     CycleColumn,
     CloseActiveApp,
     CycleAppOnGrid,
+    MoveToGridCell(usize),
     ToggleTopMost,
     CycleActiveApp(CycleDirection),
     CycleAppWidth(CycleDirection),
     CycleAppHeight(CycleDirection),
+    // the monitor follows the app to the workspace, like all MoveToWorkspace*
+    // commands without `Silently`
     MoveToWorkspace(CycleDirection),
+    // MoveToWorkspace with the follow spelled out
+    MoveToWorkspaceAndFollow(CycleDirection),
+    // the app goes, the monitor keeps showing the workspace it was on
+    MoveToWorkspaceSilently(CycleDirection),
+    // a workspace name, or its index when no workspace has that name, follows
+    MoveToWorkspaceNamed(String),
     GoToWorkspace(CycleDirection),
+    GoToWorkspaceIndex(usize),
+    GoToWorkspaceNamed(String),
+    // back to the workspace the monitor showed before, i3's back_and_forth
+    GoToWorkspacePrevious,
+    // a new workspace with this name, shown on the monitor under the cursor
+    CreateWorkspace(String),
+    RenameWorkspace(String),
+    // the active one, its windows go to the workspace before it
+    DeleteWorkspace,
+    MoveWorkspace(CycleDirection),
+    SetLayout(LayoutKind),
+    IncreaseMasterCount,
+    DecreaseMasterCount,
+    GrowMaster,
+    ShrinkMaster,
+    PromoteToMaster,
+    FocusColumn(CycleDirection),
+    MoveColumn(CycleDirection),
+    ConsumeIntoColumn(CycleDirection),
+    ExpelFromColumn,
+    CycleColumnWidth(CycleDirection),
+    IncreaseGaps,
+    DecreaseGaps,
+    ToggleGaps,
+    FocusDirection(Direction),
+    SwapDirection(Direction),
+    MoveToMonitor(MonitorTarget),
+    ToggleFloat,
+    // show the scratchpad centred on the monitor under the cursor, hide it
+    // again on the next press. launched when it has no window
+    ToggleScratchpad(String),
+    // the active window becomes that scratchpad and goes off every workspace
+    MarkScratchpad(String),
 }
 
 #[derive(Debug, NtekDes, NtekSer)]
@@ -51,6 +138,48 @@ pub struct WsGrid {
     pub x: f32,
     pub y: f32,
 }
+/// gaps for one monitor or one workspace, unset values fall back to the global ones
+#[derive(Debug, NtekDes, NtekSer)]
+pub struct GapOverride {
+    pub monitor: Option<usize>,
+    pub workspace: Option<String>,
+    pub outer_gap: Option<i32>,
+    pub inner_gap: Option<i32>,
+}
+/// workspaces of one monitor, a monitor with its own list only shows those
+#[derive(Debug, NtekDes, NtekSer)]
+pub struct MonitorWorkspaces {
+    pub monitor: usize,
+    pub workspaces: Vec<String>,
+}
+/// matches on `exe`, `class` and `title` (exact, `*` glob or `/regex/`),
+/// unset actions leave the window alone
+#[derive(Debug, Default, NtekDes, NtekSer)]
+pub struct WindowRule {
+    pub exe: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub ignore: Option<bool>,
+    pub float: Option<bool>,
+    pub top_most: Option<bool>,
+    pub workspace: Option<String>,
+    pub monitor: Option<usize>,
+    // index into `workspace_grid`
+    pub grid: Option<usize>,
+    // `#rrggbb`
+    pub border: Option<String>,
+    // the first window that matches is this scratchpad
+    pub scratchpad: Option<String>,
+}
+/// what `W::ToggleScratchpad(name)` runs when the scratchpad has no window,
+/// and its size as a share of the monitor
+#[derive(Debug, NtekDes, NtekSer)]
+pub struct ScratchpadConfig {
+    pub name: String,
+    pub launch: Option<String>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+}
 
 #[derive(Debug, NtekDes, NtekSer)]
 pub struct NtekConfig {
@@ -61,9 +190,92 @@ pub struct NtekConfig {
     pub hotkeys: HashMap<String, SomeFunc>,
     pub blacklist: Vec<String>,
     pub size_factor: Vec<f32>,
+    pub layout: Option<LayoutKind>,
+    pub master_count: Option<usize>,
+    pub master_ratio: Option<f32>,
+    pub column_width: Option<f32>,
+    pub outer_gap: Option<i32>,
+    pub inner_gap: Option<i32>,
+    pub gap_overrides: Option<Vec<GapOverride>>,
+    pub rules: Option<Vec<WindowRule>>,
+    pub monitor_workspaces: Option<Vec<MonitorWorkspaces>>,
+    pub scratchpads: Option<Vec<ScratchpadConfig>>,
+}
+impl NtekConfig {
+    /// empty workspace with the configured layout defaults
+    pub fn new_workspace(&self, name: &str) -> Workspace {
+        Workspace {
+            layout: self.layout.unwrap_or_default(),
+            master_stack: MasterStack::new(
+                self.master_count.unwrap_or(1),
+                self.master_ratio.unwrap_or(0.55),
+            ),
+            ..Workspace::new(name, Vec::new())
+        }
+    }
+    /// some monitor has a workspace called `name`
+    pub fn has_workspace(&self, name: &str) -> bool {
+        self.monitor_workspaces
+            .iter()
+            .flatten()
+            .flat_map(|m| m.workspaces.iter())
+            .chain(self.workspaces.iter())
+            .any(|n| n == name)
+    }
+    /// every workspace with the monitor it belongs to. with `monitor_workspaces`
+    /// each monitor gets its own list, `workspaces` for monitors without one.
+    /// otherwise all monitors share `workspaces`
+    pub fn workspace_list(&self, monitor_count: usize) -> Vec<(Option<usize>, String)> {
+        match self.monitor_workspaces.as_ref().filter(|m| !m.is_empty()) {
+            Some(per_monitor) => (0..monitor_count)
+                .flat_map(|monitor| {
+                    per_monitor
+                        .iter()
+                        .find(|m| m.monitor == monitor)
+                        .map_or(&self.workspaces, |m| &m.workspaces)
+                        .iter()
+                        .map(move |name| (Some(monitor), name.clone()))
+                })
+                .collect(),
+            None => self
+                .workspaces
+                .iter()
+                .map(|name| (None, name.clone()))
+                .collect(),
+        }
+    }
+    pub fn grid_cells(&self) -> Vec<GridCell> {
+        self.workspace_grid
+            .iter()
+            .map(|g| (g.x, g.y, g.width, g.height))
+            .collect()
+    }
+    pub fn gap_settings(&self) -> GapSettings {
+        let base = Gaps::new(self.outer_gap.unwrap_or(0), self.inner_gap.unwrap_or(0));
+        let mut settings = GapSettings {
+            base,
+            ..Default::default()
+        };
+        for ov in self.gap_overrides.iter().flatten() {
+            let gaps = Gaps::new(
+                ov.outer_gap.unwrap_or(base.outer),
+                ov.inner_gap.unwrap_or(base.inner),
+            );
+            if let Some(workspace) = &ov.workspace {
+                settings.workspaces.insert(workspace.clone(), gaps);
+            } else if let Some(monitor) = ov.monitor {
+                settings.monitors.insert(monitor, gaps);
+            }
+        }
+        settings
+    }
 }
 impl WF {
-    pub fn do_stuff(&self, handler: Arc<OverlayManager>, conf: Arc<NtekConfig>) {
+    pub fn do_stuff(
+        &self,
+        handler: Arc<OverlayManager>,
+        conf: Arc<NtekConfig>,
+    ) -> anyhow::Result<()> {
         match self {
             WF::MoveActiveApp(direction) => {
                 handler.with_handler(|hd| {
@@ -93,16 +305,21 @@ impl WF {
             }
             WF::Debug => {}
             WF::CycleAppOnGrid => {
-                let grid = conf
-                    .workspace_grid
-                    .iter()
-                    .map(|fs| (fs.x, fs.y, fs.width, fs.height))
-                    .collect::<Vec<_>>();
+                let grid = conf.grid_cells();
 
                 handler.with_handler(|hd| {
                     hd.cycle_app_on_grid(&grid);
                 });
             }
+            WF::MoveToGridCell(index) => {
+                let grid = conf.grid_cells();
+                if *index >= grid.len() {
+                    anyhow::bail!("workspace_grid has no cell {index}");
+                }
+                handler.with_handler(|hd| {
+                    hd.move_to_grid_cell(*index, &grid);
+                });
+            }
             WF::CycleAppHeight(direction) => {
                 handler.with_handler(|hd| {
                     hd.cycle_app_height(direction.as_str());
@@ -120,10 +337,7 @@ impl WF {
             }
             WF::CycleActiveApp(direction) => {
                 handler.with_handler(|hd| {
-
-                    // if let Err(err) = handler.lock().cycle_active_app(direction.as_str()) {
-                    //     eprintln!("Error on cycle active app {err}")
-                    // }
+                    hd.cycle_active_app(direction);
                 });
             }
             WF::ToggleTopMost => {
@@ -131,23 +345,176 @@ impl WF {
                     hd.toggle_top_most();
                 });
             }
-            WF::MoveToWorkspace(direction) => {
-                handler.with_handler(|hd| {
-                    if let Err(err) = hd.move_active_to_workspace(direction) {
-                        eprintln!("Error move app to workspace {direction:?} {err}")
-                    }
-                });
+            WF::MoveToWorkspace(direction) | WF::MoveToWorkspaceAndFollow(direction) => {
+                handler
+                    .with_handler(|hd| hd.move_active_to_workspace(direction, true))
+                    .with_context(|| format!("move app to workspace {direction:?}"))?;
+            }
+            WF::MoveToWorkspaceSilently(direction) => {
+                handler
+                    .with_handler(|hd| hd.move_active_to_workspace(direction, false))
+                    .with_context(|| format!("move app to workspace {direction:?}"))?;
+            }
+            WF::MoveToWorkspaceNamed(name) => {
+                handler
+                    .with_handler(|hd| {
+                        let index = hd
+                            .find_workspace(name)
+                            .ok_or(anyhow::anyhow!("no workspace `{name}`"))?;
+                        hd.move_active_to_workspace_index(index, true)
+                    })
+                    .with_context(|| format!("move app to workspace {name}"))?;
             }
             WF::GoToWorkspace(worskpace) => {
                 handler.with_handler(|hd| {
                     hd.go_to_workspace(worskpace);
                 });
             }
+            WF::GoToWorkspaceIndex(index) => {
+                handler
+                    .with_handler(|hd| hd.go_to_workspace_index(*index))
+                    .with_context(|| format!("no workspace {index}"))?;
+            }
+            WF::GoToWorkspaceNamed(name) => {
+                handler
+                    .with_handler(|hd| {
+                        let index = hd.find_workspace(name)?;
+                        hd.show_workspace(index)
+                    })
+                    .with_context(|| format!("no workspace `{name}`"))?;
+            }
+            WF::GoToWorkspacePrevious => {
+                handler.with_handler(|hd| {
+                    hd.go_to_previous_workspace();
+                });
+            }
+            WF::CreateWorkspace(name) => {
+                handler
+                    .with_handler(|hd| hd.create_workspace(name, hd.get_active_monitor()))
+                    .with_context(|| format!("create workspace {name}"))?;
+            }
+            WF::RenameWorkspace(name) => {
+                handler
+                    .with_handler(|hd| hd.rename_active_workspace(name))
+                    .with_context(|| format!("rename workspace to {name}"))?;
+            }
+            WF::DeleteWorkspace => {
+                handler
+                    .with_handler(|hd| hd.delete_active_workspace())
+                    .context("delete workspace")?;
+            }
+            WF::MoveWorkspace(direction) => {
+                handler.with_handler(|hd| {
+                    hd.move_active_workspace(direction);
+                });
+            }
             WF::CloseActiveApp => {
                 handler.with_handler(|hd| {
                     hd.close_active_app();
                 });
             }
+            WF::SetLayout(layout) => {
+                handler.with_handler(|hd| {
+                    hd.set_layout(*layout);
+                });
+            }
+            WF::IncreaseMasterCount => {
+                handler.with_handler(|hd| {
+                    hd.update_master_stack(|ms| ms.increase_master_count());
+                });
+            }
+            WF::DecreaseMasterCount => {
+                handler.with_handler(|hd| {
+                    hd.update_master_stack(|ms| ms.decrease_master_count());
+                });
+            }
+            WF::GrowMaster => {
+                handler.with_handler(|hd| {
+                    hd.update_master_stack(|ms| ms.resize_master(MASTER_RATIO_STEP));
+                });
+            }
+            WF::ShrinkMaster => {
+                handler.with_handler(|hd| {
+                    hd.update_master_stack(|ms| ms.resize_master(-MASTER_RATIO_STEP));
+                });
+            }
+            WF::PromoteToMaster => {
+                handler.with_handler(|hd| {
+                    hd.promote_to_master();
+                });
+            }
+            WF::FocusColumn(direction) => {
+                handler.with_handler(|hd| {
+                    hd.update_scrolling_strip(|strip| strip.focus_column(direction));
+                });
+            }
+            WF::MoveColumn(direction) => {
+                handler.with_handler(|hd| {
+                    hd.update_scrolling_strip(|strip| strip.move_column(direction));
+                });
+            }
+            WF::ConsumeIntoColumn(direction) => {
+                handler.with_handler(|hd| {
+                    hd.update_scrolling_strip(|strip| strip.consume(direction));
+                });
+            }
+            WF::ExpelFromColumn => {
+                handler.with_handler(|hd| {
+                    hd.update_scrolling_strip(|strip| strip.expel());
+                });
+            }
+            WF::CycleColumnWidth(direction) => {
+                handler.with_handler(|hd| {
+                    let size_factor = hd.size_factor.clone();
+                    hd.update_scrolling_strip(|strip| strip.cycle_width(direction, &size_factor));
+                });
+            }
+            WF::FocusDirection(direction) => {
+                handler.with_handler(|hd| {
+                    hd.focus_direction(direction);
+                });
+            }
+            WF::SwapDirection(direction) => {
+                handler.with_handler(|hd| {
+                    hd.swap_direction(direction);
+                });
+            }
+            WF::MoveToMonitor(target) => {
+                handler.with_handler(|hd| {
+                    hd.move_to_monitor(target);
+                });
+            }
+            WF::ToggleFloat => {
+                handler.with_handler(|hd| {
+                    hd.toggle_float();
+                });
+            }
+            WF::IncreaseGaps => {
+                handler.with_handler(|hd| {
+                    hd.update_gaps(|gaps| gaps.adjust(GAP_STEP));
+                });
+            }
+            WF::DecreaseGaps => {
+                handler.with_handler(|hd| {
+                    hd.update_gaps(|gaps| gaps.adjust(-GAP_STEP));
+                });
+            }
+            WF::ToggleGaps => {
+                handler.with_handler(|hd| {
+                    hd.update_gaps(|gaps| gaps.toggle());
+                });
+            }
+            WF::ToggleScratchpad(name) => {
+                handler
+                    .with_handler(|hd| hd.toggle_scratchpad(name))
+                    .with_context(|| format!("toggle scratchpad {name}"))?;
+            }
+            WF::MarkScratchpad(name) => {
+                handler
+                    .with_handler(|hd| hd.mark_scratchpad(name))
+                    .with_context(|| format!("mark scratchpad {name}"))?;
+            }
         }
+        Ok(())
     }
 }
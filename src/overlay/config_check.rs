@@ -0,0 +1,495 @@
+use std::fmt::{self, Display};
+
+use crate::overlay::{config::NtekConfig, rules::Rule};
+
+//==============================================================================//
+// tag         : DIAGNOSTICS
+// description : things that parse fine but won't work
+//==============================================================================//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // e.g. `workspace_grid[2].x`
+    pub key: String,
+    pub message: String,
+    // line of the top level key, filled in when the source text is known
+    pub line: Option<usize>,
+}
+impl Diagnostic {
+    fn new(severity: Severity, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            key: key.into(),
+            message: message.into(),
+            line: None,
+        }
+    }
+    fn error(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, key, message)
+    }
+    fn warning(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, key, message)
+    }
+    /// `workspace_grid` for `workspace_grid[2].x`
+    pub fn top_level_key(&self) -> &str {
+        self.key
+            .split(['[', '.'])
+            .next()
+            .unwrap_or(self.key.as_str())
+    }
+}
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: `{}`: {}", self.severity, self.key, self.message)
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+//==============================================================================//
+// tag         : HOTKEYS
+// description : what a hotkey string may look like, `C-S-A-pagedown`.
+//               tsck-kee parses them for real, so anything this doesn't know
+//               is only a warning and the binding is still handed over
+//==============================================================================//
+const MODIFIERS: &[&str] = &["C", "S", "A", "W"];
+const NAMED_KEYS: &[&str] = &[
+    "left",
+    "right",
+    "up",
+    "down",
+    "pageup",
+    "pagedown",
+    "home",
+    "end",
+    "insert",
+    "delete",
+    "backspace",
+    "tab",
+    "enter",
+    "escape",
+    "space",
+    "comma",
+    "dot",
+    "minus",
+    "equal",
+    "slash",
+    "backslash",
+    "semicolon",
+    "quote",
+    "backquote",
+    "bracketleft",
+    "bracketright",
+];
+
+/// why `hotkey` can't be bound, `None` when it looks fine
+pub fn hotkey_problem(hotkey: &str) -> Option<String> {
+    let parts: Vec<&str> = hotkey.split('-').collect();
+    let (key, modifiers) = parts.split_last()?;
+    if modifiers.is_empty() {
+        return Some("needs at least one modifier (C, S, A or W)".into());
+    }
+    for (i, modifier) in modifiers.iter().enumerate() {
+        if !MODIFIERS.contains(modifier) {
+            return Some(format!("unknown modifier `{modifier}`, use C, S, A or W"));
+        }
+        if modifiers[..i].contains(modifier) {
+            return Some(format!("modifier `{modifier}` used twice"));
+        }
+    }
+    let is_char = key.len() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric());
+    let is_function = key
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<u8>().ok())
+        .is_some_and(|n| (1..=24).contains(&n));
+    if !(is_char || is_function || NAMED_KEYS.contains(key)) {
+        return Some(format!("unknown key `{key}`"));
+    }
+    None
+}
+
+//==============================================================================//
+// tag         : VALIDATION
+// description : runs after `ntek::from_str`, collects every problem at once
+//==============================================================================//
+fn unit_range(value: f32) -> bool {
+    (0.0..=1.0).contains(&value)
+}
+/// names of one workspace list, `key` is where the list is
+fn check_workspace_names(key: &str, names: &[String], out: &mut Vec<Diagnostic>) {
+    if names.is_empty() {
+        out.push(Diagnostic::error(key, "needs at least one workspace"));
+    }
+    for (i, name) in names.iter().enumerate() {
+        if name.trim().is_empty() {
+            out.push(Diagnostic::error(format!("{key}[{i}]"), "empty name"));
+        }
+        if let Some(first) = names[..i].iter().position(|n| n == name) {
+            out.push(Diagnostic::error(
+                format!("{key}[{i}]"),
+                format!("\"{name}\" is already used by {key}[{first}]"),
+            ));
+        }
+    }
+}
+
+pub fn validate(config: &NtekConfig) -> Vec<Diagnostic> {
+    let mut out = vec![];
+
+    if config.size_factor.is_empty() {
+        out.push(Diagnostic::error(
+            "size_factor",
+            "needs at least one value, W::CycleAppWidth and fake maximize index into it",
+        ));
+    }
+    for (i, factor) in config.size_factor.iter().enumerate() {
+        if *factor <= 0.0 || *factor > 1.0 {
+            out.push(Diagnostic::error(
+                format!("size_factor[{i}]"),
+                format!("{factor} is outside 0..1"),
+            ));
+        }
+    }
+
+    if config.workspace_grid.is_empty() {
+        out.push(Diagnostic::warning(
+            "workspace_grid",
+            "is empty, W::CycleAppOnGrid won't do anything",
+        ));
+    }
+    for (i, cell) in config.workspace_grid.iter().enumerate() {
+        for (name, value) in [
+            ("x", cell.x),
+            ("y", cell.y),
+            ("width", cell.width),
+            ("height", cell.height),
+        ] {
+            if !unit_range(value) {
+                out.push(Diagnostic::error(
+                    format!("workspace_grid[{i}].{name}"),
+                    format!("{value} is outside 0..1"),
+                ));
+            }
+        }
+        if cell.x + cell.width > 1.0 + f32::EPSILON || cell.y + cell.height > 1.0 + f32::EPSILON {
+            out.push(Diagnostic::warning(
+                format!("workspace_grid[{i}]"),
+                "cell sticks out of the monitor",
+            ));
+        }
+    }
+
+    check_workspace_names("workspaces", &config.workspaces, &mut out);
+    let per_monitor = config.monitor_workspaces.as_deref().unwrap_or_default();
+    for (i, monitor) in per_monitor.iter().enumerate() {
+        let key = format!("monitor_workspaces[{i}]");
+        if per_monitor[..i]
+            .iter()
+            .any(|m| m.monitor == monitor.monitor)
+        {
+            out.push(Diagnostic::error(
+                format!("{key}.monitor"),
+                format!("monitor {} already has its workspaces", monitor.monitor),
+            ));
+        }
+        check_workspace_names(&format!("{key}.workspaces"), &monitor.workspaces, &mut out);
+    }
+
+    let mut hotkeys: Vec<&String> = config.hotkeys.keys().collect();
+    hotkeys.sort();
+    for hotkey in hotkeys {
+        if let Some(problem) = hotkey_problem(hotkey) {
+            out.push(Diagnostic::warning(format!("hotkeys.{hotkey}"), problem));
+        }
+    }
+
+    if config.master_count == Some(0) {
+        out.push(Diagnostic::warning(
+            "master_count",
+            "0 puts every window in the stack",
+        ));
+    }
+    if let Some(ratio) = config.master_ratio {
+        if ratio <= 0.0 || ratio >= 1.0 {
+            out.push(Diagnostic::error(
+                "master_ratio",
+                format!("{ratio} is outside 0..1"),
+            ));
+        }
+    }
+    if let Some(width) = config.column_width {
+        if width <= 0.0 || width > 1.0 {
+            out.push(Diagnostic::error(
+                "column_width",
+                format!("{width} is outside 0..1"),
+            ));
+        }
+    }
+    for (key, gap) in [
+        ("outer_gap", config.outer_gap),
+        ("inner_gap", config.inner_gap),
+    ] {
+        if gap.is_some_and(|g| g < 0) {
+            out.push(Diagnostic::error(key, "can't be negative"));
+        }
+    }
+    for (i, ov) in config.gap_overrides.iter().flatten().enumerate() {
+        let key = format!("gap_overrides[{i}]");
+        match (&ov.monitor, &ov.workspace) {
+            (None, None) => out.push(Diagnostic::warning(
+                key.clone(),
+                "has neither `monitor` nor `workspace`, it is ignored",
+            )),
+            (Some(_), Some(_)) => out.push(Diagnostic::warning(
+                key.clone(),
+                "has both `monitor` and `workspace`, only `workspace` is used",
+            )),
+            _ => {}
+        }
+        if let Some(name) = &ov.workspace {
+            if !config.has_workspace(name) {
+                out.push(Diagnostic::warning(
+                    format!("{key}.workspace"),
+                    format!("no workspace named \"{name}\""),
+                ));
+            }
+        }
+        for (name, gap) in [("outer_gap", ov.outer_gap), ("inner_gap", ov.inner_gap)] {
+            if gap.is_some_and(|g| g < 0) {
+                out.push(Diagnostic::error(
+                    format!("{key}.{name}"),
+                    "can't be negative",
+                ));
+            }
+        }
+    }
+
+    let grid = config.grid_cells();
+    for (i, rule) in config.rules.iter().flatten().enumerate() {
+        let key = format!("rules[{i}]");
+        if rule.exe.is_none() && rule.class.is_none() && rule.title.is_none() {
+            out.push(Diagnostic::warning(
+                key.clone(),
+                "has no `exe`, `class` or `title`, it never matches",
+            ));
+        }
+        if let Err(err) = Rule::from_config(rule, &grid) {
+            out.push(Diagnostic::error(
+                format!("{key}.{}", err.field),
+                err.message,
+            ));
+        }
+        if let Some(name) = &rule.workspace {
+            if !config.has_workspace(name) {
+                out.push(Diagnostic::warning(
+                    format!("{key}.workspace"),
+                    format!("no workspace named \"{name}\""),
+                ));
+            }
+        }
+        if rule.scratchpad.is_some() && (rule.workspace.is_some() || rule.grid.is_some()) {
+            out.push(Diagnostic::warning(
+                format!("{key}.scratchpad"),
+                "a scratchpad is on no workspace and always centred, `workspace` and `grid` are ignored",
+            ));
+        }
+    }
+
+    let scratchpads = config.scratchpads.as_deref().unwrap_or_default();
+    for (i, pad) in scratchpads.iter().enumerate() {
+        let key = format!("scratchpads[{i}]");
+        if pad.name.trim().is_empty() {
+            out.push(Diagnostic::error(format!("{key}.name"), "empty name"));
+        }
+        if let Some(first) = scratchpads[..i].iter().position(|p| p.name == pad.name) {
+            out.push(Diagnostic::error(
+                format!("{key}.name"),
+                format!("\"{}\" is already used by scratchpads[{first}]", pad.name),
+            ));
+        }
+        for (name, value) in [("width", pad.width), ("height", pad.height)] {
+            if value.is_some_and(|v| v <= 0.0 || v > 1.0) {
+                out.push(Diagnostic::error(
+                    format!("{key}.{name}"),
+                    format!("{} is outside 0..1", value.unwrap_or_default()),
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::config::{
+        MonitorWorkspaces, ScratchpadConfig, SomeFunc, WF, WindowRule, WsGrid,
+    };
+    use std::collections::HashMap;
+
+    fn config() -> NtekConfig {
+        NtekConfig {
+            workspace_grid: vec![WsGrid {
+                width: 0.5,
+                height: 1.0,
+                x: 0.5,
+                y: 0.0,
+            }],
+            workspaces: vec!["Work".into(), "Browsing".into()],
+            move_inc: 50,
+            size_inc: 50,
+            hotkeys: HashMap::from([("C-S-w".to_string(), SomeFunc::W(WF::CloseActiveApp))]),
+            blacklist: vec![],
+            size_factor: vec![1.0, 0.5],
+            layout: None,
+            master_count: None,
+            master_ratio: None,
+            column_width: None,
+            outer_gap: None,
+            inner_gap: None,
+            gap_overrides: None,
+            rules: None,
+            monitor_workspaces: None,
+            scratchpads: None,
+        }
+    }
+    fn keys(diagnostics: &[Diagnostic]) -> Vec<(Severity, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.severity, d.key.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_config_is_clean() {
+        assert!(validate(&config()).is_empty());
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let mut config = config();
+        config.size_factor.clear();
+        config.workspace_grid[0].x = 1.5;
+        config.workspaces.push("Work".into());
+        config
+            .hotkeys
+            .insert("C-C-x".into(), SomeFunc::W(WF::Debug));
+        config.rules = Some(vec![
+            WindowRule {
+                float: Some(true),
+                ..Default::default()
+            },
+            WindowRule {
+                title: Some("/(/".into()),
+                workspace: Some("Games".into()),
+                ..Default::default()
+            },
+        ]);
+        let diagnostics = validate(&config);
+        assert_eq!(
+            keys(&diagnostics),
+            vec![
+                (Severity::Error, "size_factor"),
+                (Severity::Error, "workspace_grid[0].x"),
+                (Severity::Warning, "workspace_grid[0]"),
+                (Severity::Error, "workspaces[2]"),
+                (Severity::Warning, "hotkeys.C-C-x"),
+                (Severity::Warning, "rules[0]"),
+                (Severity::Error, "rules[1].title"),
+                (Severity::Warning, "rules[1].workspace"),
+            ]
+        );
+        assert!(has_errors(&diagnostics));
+        assert_eq!(diagnostics[1].top_level_key(), "workspace_grid");
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "error: `workspaces[2]`: \"Work\" is already used by workspaces[0]"
+        );
+    }
+
+    #[test]
+    fn monitors_with_their_own_workspaces() {
+        let mut config = config();
+        config.monitor_workspaces = Some(vec![
+            MonitorWorkspaces {
+                monitor: 1,
+                workspaces: vec!["Chat".into(), "Music".into(), "Chat".into()],
+            },
+            MonitorWorkspaces {
+                monitor: 1,
+                workspaces: vec![],
+            },
+        ]);
+        config.rules = Some(vec![WindowRule {
+            exe: Some("slack.exe".into()),
+            workspace: Some("Chat".into()),
+            ..Default::default()
+        }]);
+        assert_eq!(
+            keys(&validate(&config)),
+            vec![
+                (Severity::Error, "monitor_workspaces[0].workspaces[2]"),
+                (Severity::Error, "monitor_workspaces[1].monitor"),
+                (Severity::Error, "monitor_workspaces[1].workspaces"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scratchpads() {
+        let mut config = config();
+        let pad = |name: &str, width| ScratchpadConfig {
+            name: name.into(),
+            launch: Some("wezterm-gui.exe".into()),
+            width,
+            height: None,
+        };
+        config.scratchpads = Some(vec![
+            pad("term", Some(0.5)),
+            pad("term", None),
+            pad("notes", Some(1.5)),
+        ]);
+        config.rules = Some(vec![WindowRule {
+            exe: Some("wezterm-gui.exe".into()),
+            scratchpad: Some("term".into()),
+            grid: Some(0),
+            ..Default::default()
+        }]);
+        assert_eq!(
+            keys(&validate(&config)),
+            vec![
+                (Severity::Warning, "rules[0].scratchpad"),
+                (Severity::Error, "scratchpads[1].name"),
+                (Severity::Error, "scratchpads[2].width"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hotkey_strings() {
+        assert_eq!(hotkey_problem("C-S-A-pagedown"), None);
+        assert_eq!(hotkey_problem("C-S-f12"), None);
+        assert_eq!(hotkey_problem("W-1"), None);
+        assert!(hotkey_problem("x").is_some());
+        assert!(hotkey_problem("C-S-pgdn").is_some());
+        assert!(hotkey_problem("Ctrl-x").is_some());
+        assert!(hotkey_problem("C-S-f25").is_some());
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    fmt::{self, Debug, Display},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::overlay::{
+    config::NtekConfig,
+    config_check::{self, Diagnostic, Severity},
+};
+
+/// environment variable pointing at a config file
+pub const CONFIG_ENV: &str = "TSCK_WINDOW_CONFIG";
+/// used when no config file is found anywhere
+pub const DEFAULT_CONFIG: &str = include_str!("../../config.ntek");
+const CONFIG_DIR: &str = "tsck-window";
+const CONFIG_NAME: &str = "config.ntek";
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+//==============================================================================//
+// tag         : DISCOVERY
+// description : --config <path>, then $TSCK_WINDOW_CONFIG, then the per-user
+//               config dir, then the config baked into the binary
+//==============================================================================//
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Cli(PathBuf),
+    Env(PathBuf),
+    UserDir(PathBuf),
+    Embedded,
+}
+impl ConfigSource {
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ConfigSource::Cli(path) | ConfigSource::Env(path) | ConfigSource::UserDir(path) => {
+                Some(path)
+            }
+            ConfigSource::Embedded => None,
+        }
+    }
+    pub fn read(&self) -> Result<String, ConfigError> {
+        match self.path() {
+            Some(path) => std::fs::read_to_string(path).map_err(|err| ConfigError {
+                file: self.to_string(),
+                line: None,
+                key: None,
+                message: format!("can't read config: {err}"),
+            }),
+            None => Ok(DEFAULT_CONFIG.to_string()),
+        }
+    }
+}
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) => write!(f, "{}", path.display()),
+            None => write!(f, "<embedded config.ntek>"),
+        }
+    }
+}
+
+/// value of `--config <path>` or `--config=<path>`
+pub fn config_path_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// `%APPDATA%\tsck-window\config.ntek`, `$XDG_CONFIG_HOME/tsck-window/config.ntek`
+/// or `~/.config/tsck-window/config.ntek`
+pub fn user_config_path() -> Option<PathBuf> {
+    let env = |key| std::env::var_os(key).filter(|v| !v.is_empty());
+    let base = if cfg!(windows) {
+        env("APPDATA").map(PathBuf::from)
+    } else {
+        env("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }?;
+    Some(base.join(CONFIG_DIR).join(CONFIG_NAME))
+}
+
+/// an explicit path (cli or env) is used even when missing, so a typo
+/// shows up as an error instead of silently falling back
+pub fn discover(cli: Option<PathBuf>) -> ConfigSource {
+    if let Some(path) = cli {
+        return ConfigSource::Cli(path);
+    }
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|v| !v.is_empty()) {
+        return ConfigSource::Env(PathBuf::from(path));
+    }
+    match user_config_path() {
+        Some(path) if path.is_file() => ConfigSource::UserDir(path),
+        _ => ConfigSource::Embedded,
+    }
+}
+
+//==============================================================================//
+// tag         : ERRORS
+// description : where in the file things went wrong
+//==============================================================================//
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub key: Option<String>,
+    pub message: String,
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " `{key}`")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+impl std::error::Error for ConfigError {}
+
+/// top level `key = value` entries and the (1 based) line they start on
+pub fn top_level_keys(source: &str) -> Vec<(usize, String)> {
+    let mut keys = vec![];
+    let mut depth = 0i32;
+    for (index, line) in source.lines().enumerate() {
+        let mut in_string = false;
+        let mut code = String::new();
+        for c in line.chars() {
+            match c {
+                '"' => in_string = !in_string,
+                '#' if !in_string => break,
+                _ => {}
+            }
+            code.push(c);
+        }
+        if depth == 0 {
+            if let Some((key, _)) = code.split_once('=') {
+                let key = key.trim();
+                if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    keys.push((index + 1, key.to_string()));
+                }
+            }
+        }
+        let mut in_string = false;
+        for c in code.chars() {
+            match c {
+                '"' => in_string = !in_string,
+                '[' | '{' | '(' if !in_string => depth += 1,
+                ']' | '}' | ')' if !in_string => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+    keys
+}
+
+/// the (1 based) line a parser error points at, if it knows one
+pub trait ErrorLine {
+    fn error_line(&self) -> Option<usize>;
+}
+impl ErrorLine for ntek::Error {
+    fn error_line(&self) -> Option<usize> {
+        self.line()
+    }
+}
+
+/// the line the parser reports and the top level entry it falls in. without
+/// a line there is nothing to go on, the error says so instead of guessing
+pub fn locate(file: &str, source: &str, line: Option<usize>, message: String) -> ConfigError {
+    let Some(line) = line else {
+        return ConfigError {
+            file: file.to_string(),
+            line: None,
+            key: None,
+            message: format!("unknown location: {message}"),
+        };
+    };
+    let key = top_level_keys(source)
+        .into_iter()
+        .take_while(|(start, _)| *start <= line)
+        .last()
+        .map(|(_, key)| key);
+    ConfigError {
+        file: file.to_string(),
+        line: Some(line),
+        key,
+        message,
+    }
+}
+
+//==============================================================================//
+// tag         : LOADING
+// description : -
+//==============================================================================//
+/// read `source` and run it through `parse`, failures get located in the file
+pub fn load_with<T, E: Debug + ErrorLine>(
+    source: &ConfigSource,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, ConfigError> {
+    let text = source.read()?;
+    parse(&text).map_err(|err| {
+        locate(
+            &source.to_string(),
+            &text,
+            err.error_line(),
+            format!("{err:?}"),
+        )
+    })
+}
+
+/// parse and validate, every diagnostic gets the line of its top level key
+pub fn check(source: &ConfigSource) -> Result<(NtekConfig, Vec<Diagnostic>), ConfigError> {
+    let text = source.read()?;
+    let config = ntek::from_str::<NtekConfig>(&text).map_err(|err| {
+        locate(
+            &source.to_string(),
+            &text,
+            err.error_line(),
+            format!("{err:?}"),
+        )
+    })?;
+    let lines = top_level_keys(&text);
+    let diagnostics = config_check::validate(&config)
+        .into_iter()
+        .map(|mut d| {
+            d.line = lines
+                .iter()
+                .find(|(_, key)| key == d.top_level_key())
+                .map(|(line, _)| *line);
+            d
+        })
+        .collect();
+    Ok((config, diagnostics))
+}
+
+/// `config.ntek:12: error: `size_factor`: ...`
+pub fn format_diagnostic(source: &ConfigSource, diagnostic: &Diagnostic) -> String {
+    match diagnostic.line {
+        Some(line) => format!("{source}:{line}: {diagnostic}"),
+        None => format!("{source}: {diagnostic}"),
+    }
+}
+
+/// like `check`, but validation errors reject the config and warnings only get printed
+pub fn load(source: &ConfigSource) -> Result<NtekConfig, ConfigError> {
+    let (config, diagnostics) = check(source)?;
+    let mut errors = vec![];
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Warning => eprintln!("{}", format_diagnostic(source, &diagnostic)),
+            Severity::Error => errors.push(diagnostic),
+        }
+    }
+    match errors.first() {
+        Some(first) => Err(ConfigError {
+            file: source.to_string(),
+            line: first.line,
+            key: Some(first.key.clone()),
+            message: match errors.len() {
+                1 => first.message.clone(),
+                n => format!("{} (and {} more)", first.message, n - 1),
+            },
+        }),
+        None => Ok(config),
+    }
+}
+
+/// discover and load, returning where the config came from as well
+pub fn load_config(cli: Option<PathBuf>) -> Result<(NtekConfig, ConfigSource), ConfigError> {
+    let source = discover(cli);
+    let config = load(&source)?;
+    Ok((config, source))
+}
+
+//==============================================================================//
+// tag         : WATCH
+// description : poll the config file and hand over every change
+//==============================================================================//
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// reload `source` whenever its file changes, the embedded config never does.
+/// a broken edit is passed on as the error, it's up to `on_change` to keep the old one.
+pub fn watch(
+    source: ConfigSource,
+    on_change: impl Fn(Result<NtekConfig, ConfigError>) + Send + 'static,
+) {
+    let Some(path) = source.path().map(Path::to_path_buf) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let mut last = modified(&path);
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let now = modified(&path);
+            if now.is_none() || now == last {
+                continue;
+            }
+            last = now;
+            on_change(load(&source));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"# comment = not a key
+size_factor = [1.0, 0.5]
+workspace_grid = [
+      { width: 1.0, height: 1.0, x: 0.0,  y  : 0.0   },
+]
+workspaces = [
+  "Work = Play",
+]
+move_inc = 50
+hotkeys = {
+  C-S-right : W::MoveActiveApp(Right),
+}
+"#;
+
+    #[test]
+    fn finds_top_level_keys_with_lines() {
+        assert_eq!(
+            top_level_keys(SOURCE),
+            vec![
+                (2, "size_factor".to_string()),
+                (3, "workspace_grid".to_string()),
+                (6, "workspaces".to_string()),
+                (9, "move_inc".to_string()),
+                (10, "hotkeys".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_config_flag() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            config_path_from_args(args(&["--config", "a.ntek"])),
+            Some(PathBuf::from("a.ntek"))
+        );
+        assert_eq!(
+            config_path_from_args(args(&["x", "--config=b.ntek"])),
+            Some(PathBuf::from("b.ntek"))
+        );
+        assert_eq!(config_path_from_args(args(&["--config"])), None);
+        assert_eq!(config_path_from_args(args(&[])), None);
+    }
+
+    #[test]
+    fn explicit_path_wins() {
+        let path = PathBuf::from("missing.ntek");
+        assert_eq!(discover(Some(path.clone())), ConfigSource::Cli(path));
+    }
+
+    #[test]
+    fn locates_the_entry_of_the_reported_line() {
+        let err = locate("config.ntek", SOURCE, Some(9), "expected i32".into());
+        assert_eq!(err.line, Some(9));
+        assert_eq!(err.key.as_deref(), Some("move_inc"));
+        assert_eq!(err.to_string(), "config.ntek:9 `move_inc`: expected i32");
+        // inside a multi line value
+        let err = locate("c.ntek", SOURCE, Some(4), "unexpected token".into());
+        assert_eq!(
+            (err.line, err.key.as_deref()),
+            (Some(4), Some("workspace_grid"))
+        );
+    }
+
+    #[test]
+    fn no_line_is_an_unknown_location() {
+        // the message isn't read for a position, whatever it says
+        let err = locate(
+            "c.ntek",
+            SOURCE,
+            None,
+            "unexpected multiline value 3".into(),
+        );
+        assert_eq!((err.line, err.key), (None, None));
+        assert_eq!(
+            err.to_string(),
+            "c.ntek: unknown location: unexpected multiline value 3"
+        );
+    }
+
+    #[test]
+    fn embedded_config_parses() {
+        assert!(load(&ConfigSource::Embedded).is_ok());
+    }
+}
@@ -0,0 +1,182 @@
+use flume::{Receiver, Sender, TrySendError};
+use parking_lot::Mutex;
+use std::sync::OnceLock;
+
+use crate::overlay::{app_info::AppInfo, workspaces::WorkspaceInfo};
+
+// events a subscriber can fall behind by before it starts missing them
+const QUEUE_SIZE: usize = 256;
+
+//==============================================================================//
+// tag         : EVENTS
+// description : what the manager sees, for anyone listening over IPC
+//==============================================================================//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Focus,
+    Workspace,
+    Create,
+    Destroy,
+    Title,
+    Monitor,
+    ConfigReload,
+}
+
+/// `{"event": "focus", "app": {..}, "workspace": {..}}`, one per line on the wire
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Focus {
+        app: AppInfo,
+        workspace: Option<WorkspaceInfo>,
+    },
+    /// `monitor` switched to `workspace`
+    Workspace {
+        monitor: usize,
+        workspace: Option<WorkspaceInfo>,
+    },
+    Create {
+        app: AppInfo,
+        workspace: Option<WorkspaceInfo>,
+    },
+    /// `workspace` is where the window was, without it
+    Destroy {
+        app: AppInfo,
+        workspace: Option<WorkspaceInfo>,
+    },
+    Title {
+        app: AppInfo,
+        workspace: Option<WorkspaceInfo>,
+    },
+    /// focus moved over to another monitor
+    Monitor {
+        monitor: usize,
+        workspace: Option<WorkspaceInfo>,
+    },
+    ConfigReload {
+        workspaces: Vec<WorkspaceInfo>,
+    },
+}
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::Focus { .. } => EventKind::Focus,
+            Event::Workspace { .. } => EventKind::Workspace,
+            Event::Create { .. } => EventKind::Create,
+            Event::Destroy { .. } => EventKind::Destroy,
+            Event::Title { .. } => EventKind::Title,
+            Event::Monitor { .. } => EventKind::Monitor,
+            Event::ConfigReload { .. } => EventKind::ConfigReload,
+        }
+    }
+}
+
+struct Subscriber {
+    // empty means everything
+    kinds: Vec<EventKind>,
+    sender: Sender<Event>,
+}
+impl Subscriber {
+    fn wants(&self, kind: EventKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+}
+
+/// publishing never waits, a subscriber that can't keep up misses events and
+/// one that went away is dropped on the next publish
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+impl EventBus {
+    pub fn subscribe(&self, kinds: Vec<EventKind>) -> Receiver<Event> {
+        let (sender, receiver) = flume::bounded(QUEUE_SIZE);
+        self.subscribers.lock().push(Subscriber { kinds, sender });
+        receiver
+    }
+    pub fn wants(&self, kind: EventKind) -> bool {
+        self.subscribers.lock().iter().any(|s| s.wants(kind))
+    }
+    pub fn publish(&self, event: Event) {
+        let kind = event.kind();
+        self.subscribers.lock().retain(|s| {
+            if !s.wants(kind) {
+                return !s.sender.is_disconnected();
+            }
+            !matches!(
+                s.sender.try_send(event.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+    }
+}
+
+static EVENT_BUS: OnceLock<EventBus> = OnceLock::new();
+
+pub fn bus() -> &'static EventBus {
+    EVENT_BUS.get_or_init(EventBus::default)
+}
+
+/// `event` is only built when somebody listens for `kind`
+pub fn publish(kind: EventKind, event: impl FnOnce() -> Event) {
+    let bus = bus();
+    if bus.wants(kind) {
+        bus.publish(event());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(monitor: usize) -> Event {
+        Event::Monitor {
+            monitor,
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn subscribers_get_what_they_asked_for() {
+        let bus = EventBus::default();
+        let reloads = bus.subscribe(vec![EventKind::ConfigReload]);
+        assert!(!bus.wants(EventKind::Focus));
+        let all = bus.subscribe(vec![]);
+        assert!(bus.wants(EventKind::Focus));
+
+        bus.publish(monitor(1));
+        bus.publish(Event::ConfigReload { workspaces: vec![] });
+        assert_eq!(all.drain().count(), 2);
+        assert_eq!(
+            reloads.drain().map(|e| e.kind()).collect::<Vec<_>>(),
+            [EventKind::ConfigReload]
+        );
+    }
+
+    #[test]
+    fn slow_and_gone_subscribers_dont_hold_anything_up() {
+        let bus = EventBus::default();
+        let slow = bus.subscribe(vec![]);
+        let gone = bus.subscribe(vec![EventKind::Focus]);
+        drop(gone);
+        for i in 0..QUEUE_SIZE * 2 {
+            bus.publish(monitor(i));
+        }
+        assert_eq!(bus.subscribers.lock().len(), 1);
+        assert_eq!(slow.len(), QUEUE_SIZE);
+        assert!(matches!(slow.recv(), Ok(Event::Monitor { monitor: 0, .. })));
+    }
+
+    #[test]
+    fn events_say_what_they_are() {
+        assert_eq!(
+            serde_json::to_string(&monitor(1)).unwrap(),
+            r#"{"event":"monitor","monitor":1,"workspace":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<EventKind>>(r#"["focus","config_reload"]"#).unwrap(),
+            [EventKind::Focus, EventKind::ConfigReload]
+        );
+    }
+}
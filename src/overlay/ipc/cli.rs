@@ -0,0 +1,287 @@
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::overlay::{
+    config::{CycleDirection, LayoutKind, WF},
+    events::EventKind,
+    ipc::protocol::{Query, Request},
+};
+
+pub const USAGE: &str = "usage: tsckc [--json] <command>
+
+  ws next | prev                  show the next / previous workspace
+  ws go <name | index>            show that workspace
+  ws back                         show the workspace that was showing before
+  ws new <name>                   make a workspace and show it
+  ws rename <name>                rename the workspace showing
+  ws delete                       drop it, its windows go to the one before
+  ws left | right                 move it in the workspace list
+  app move-to-ws <name | index | next | prev>
+                                  move the active app and show that workspace
+  app send-to-ws <next | prev>    move the active app, keep showing this workspace
+  app close | float | top-most
+  grid <cell>                     put the active app on a workspace_grid cell
+  layout <kind>                   Floating, Bsp, MasterStack, Scrolling or Monocle
+  scratchpad <name>               show or hide a scratchpad, launch it if needed
+  scratchpad mark <name>          the active app becomes that scratchpad
+  query apps | workspaces | monitors | focused | state
+  reload                          re-read the config file
+  subscribe [kind..]              print events as they happen, one JSON per line:
+                                  focus, workspace, create, destroy, title,
+                                  monitor, config_reload. all of them by default
+  do <command> [args..]           anything a hotkey can do, e.g.
+                                  `do SwapDirection Left`, `do MoveToMonitor Index 1`
+
+exit codes: 0 ok, 1 the manager said no, 2 bad usage, 3 manager not running";
+
+//==============================================================================//
+// tag         : CLI
+// description : `tsckc` arguments into requests, the binary only does the io
+//==============================================================================//
+#[derive(Debug)]
+pub enum Invocation {
+    Send(Request),
+    Subscribe(Vec<EventKind>),
+}
+
+#[derive(Debug)]
+pub struct Cli {
+    pub json: bool,
+    pub invocation: Invocation,
+}
+
+/// the error is the message to print, bad usage gets the full `USAGE`
+pub fn parse(args: &[String]) -> Result<Cli, String> {
+    let json = args.iter().any(|a| a == "--json");
+    let words: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--json")
+        .collect();
+    let command =
+        |wf: WF| -> Result<Invocation, String> { Ok(Invocation::Send(Request::Command(wf))) };
+    let invocation = match words.as_slice() {
+        ["ws", "next"] => command(WF::GoToWorkspace(CycleDirection::Next)),
+        ["ws", "prev"] => command(WF::GoToWorkspace(CycleDirection::Prev)),
+        ["ws", "back"] => command(WF::GoToWorkspacePrevious),
+        ["ws", "new", name] => command(WF::CreateWorkspace(name.to_string())),
+        ["ws", "rename", name] => command(WF::RenameWorkspace(name.to_string())),
+        ["ws", "delete"] => command(WF::DeleteWorkspace),
+        ["ws", "left"] => command(WF::MoveWorkspace(CycleDirection::Prev)),
+        ["ws", "right"] => command(WF::MoveWorkspace(CycleDirection::Next)),
+        ["ws", "go", target] => command(WF::GoToWorkspaceNamed(target.to_string())),
+        ["app", "move-to-ws", "next"] => command(WF::MoveToWorkspace(CycleDirection::Next)),
+        ["app", "move-to-ws", "prev"] => command(WF::MoveToWorkspace(CycleDirection::Prev)),
+        ["app", "move-to-ws", target] => command(WF::MoveToWorkspaceNamed(target.to_string())),
+        ["app", "send-to-ws", "next"] => command(WF::MoveToWorkspaceSilently(CycleDirection::Next)),
+        ["app", "send-to-ws", "prev"] => command(WF::MoveToWorkspaceSilently(CycleDirection::Prev)),
+        ["app", "close"] => command(WF::CloseActiveApp),
+        ["app", "float"] => command(WF::ToggleFloat),
+        ["app", "top-most"] => command(WF::ToggleTopMost),
+        ["grid", cell] => match cell.parse() {
+            Ok(cell) => command(WF::MoveToGridCell(cell)),
+            Err(_) => Err(format!("grid cell `{cell}` is not a number")),
+        },
+        ["scratchpad", "mark", name] => command(WF::MarkScratchpad(name.to_string())),
+        ["scratchpad", name] => command(WF::ToggleScratchpad(name.to_string())),
+        ["layout", kind] => {
+            from_words::<LayoutKind>(&[*kind]).and_then(|k| command(WF::SetLayout(k)))
+        }
+        ["query", what] => {
+            from_words::<Query>(&[*what]).map(|q| Invocation::Send(Request::Query(q)))
+        }
+        ["reload"] => Ok(Invocation::Send(Request::Reload)),
+        ["subscribe", kinds @ ..] => kinds
+            .iter()
+            .map(|kind| from_words::<EventKind>(&[*kind]))
+            .collect::<Result<_, _>>()
+            .map(Invocation::Subscribe),
+        ["do", wf @ ..] if !wf.is_empty() => from_words::<WF>(wf).and_then(command),
+        [] => Err(USAGE.to_string()),
+        _ => Err(format!("unknown command `{}`\n\n{USAGE}", words.join(" "))),
+    }?;
+    Ok(Cli { json, invocation })
+}
+
+/// `MoveToMonitor Index 1` reads as `{"MoveToMonitor": {"Index": 1}}`, the same
+/// shape the IPC protocol takes
+fn from_words<T: DeserializeOwned>(words: &[&str]) -> Result<T, String> {
+    let (last, rest) = words.split_last().ok_or("missing argument")?;
+    let last = match last.parse::<i64>() {
+        Ok(n) => Value::from(n),
+        Err(_) => Value::from(*last),
+    };
+    let value = rest.iter().rev().fold(last, |inner, word| {
+        Value::Object(Map::from_iter([(word.to_string(), inner)]))
+    });
+    serde_json::from_value(value).map_err(|err| format!("`{}`: {err}", words.join(" ")))
+}
+
+/// plain text for people, `--json` prints the reply as is
+pub fn format_query(query: Query, data: &Value) -> String {
+    let text = |v: &Value| v.as_str().unwrap_or_default().to_string();
+    let rows = |data: &Value, row: &dyn Fn(&Value) -> String| {
+        data.as_array()
+            .map(|items| items.iter().map(row).collect::<Vec<_>>().join("\n"))
+            .unwrap_or_default()
+    };
+    let app = |app: &Value| {
+        let hwnd = app["hwnd"].to_string();
+        format!(
+            "{hwnd:<10} {:<24} {}",
+            text(&app["exe"]),
+            text(&app["title"])
+        )
+    };
+    match query {
+        Query::Apps => rows(data, &app),
+        Query::Focused if data.is_null() => "nothing focused".to_string(),
+        Query::Focused => app(data),
+        Query::Workspaces => rows(data, &|ws| {
+            let monitors = ws["active_on"].as_array().cloned().unwrap_or_default();
+            let windows = ws["windows"].as_array().map(Vec::len).unwrap_or_default();
+            let mut active = String::new();
+            if !monitors.is_empty() {
+                let monitors: Vec<String> = monitors.iter().map(Value::to_string).collect();
+                active = format!("  active on {}", monitors.join(","));
+            }
+            format!(
+                "{:<3} {:<16} {:<12} {windows} windows{active}",
+                ws["index"].to_string(),
+                text(&ws["name"]),
+                text(&ws["layout"])
+            )
+        }),
+        Query::Monitors | Query::State => serde_json::to_string_pretty(data).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::config::MonitorTarget;
+
+    fn parse_words(line: &str) -> Result<Cli, String> {
+        parse(
+            &line
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn command(line: &str) -> WF {
+        match parse_words(line).unwrap().invocation {
+            Invocation::Send(Request::Command(wf)) => wf,
+            other => panic!("{line}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ws_commands() {
+        assert!(matches!(
+            command("ws next"),
+            WF::GoToWorkspace(CycleDirection::Next)
+        ));
+        assert!(matches!(
+            command("ws go Browsing"),
+            WF::GoToWorkspaceNamed(ws) if ws == "Browsing"
+        ));
+        assert!(matches!(command("ws back"), WF::GoToWorkspacePrevious));
+        assert!(matches!(
+            command("ws new Music"),
+            WF::CreateWorkspace(ws) if ws == "Music"
+        ));
+        assert!(matches!(
+            command("ws left"),
+            WF::MoveWorkspace(CycleDirection::Prev)
+        ));
+    }
+
+    #[test]
+    fn app_commands() {
+        assert!(matches!(
+            command("app move-to-ws prev"),
+            WF::MoveToWorkspace(CycleDirection::Prev)
+        ));
+        assert!(matches!(
+            command("app move-to-ws 2"),
+            WF::MoveToWorkspaceNamed(ws) if ws == "2"
+        ));
+        assert!(matches!(
+            command("app send-to-ws next"),
+            WF::MoveToWorkspaceSilently(CycleDirection::Next)
+        ));
+        assert!(matches!(command("app float"), WF::ToggleFloat));
+    }
+
+    #[test]
+    fn grid_layout_and_scratchpad_commands() {
+        assert!(matches!(command("grid 3"), WF::MoveToGridCell(3)));
+        assert!(matches!(
+            command("layout Bsp"),
+            WF::SetLayout(LayoutKind::Bsp)
+        ));
+        assert!(matches!(
+            command("scratchpad term"),
+            WF::ToggleScratchpad(name) if name == "term"
+        ));
+        assert!(matches!(
+            command("scratchpad mark term"),
+            WF::MarkScratchpad(name) if name == "term"
+        ));
+    }
+
+    #[test]
+    fn do_takes_any_hotkey_command() {
+        assert!(matches!(command("do ToggleFloat"), WF::ToggleFloat));
+        assert!(matches!(
+            command("do MoveToMonitor Index 1"),
+            WF::MoveToMonitor(MonitorTarget::Index(1))
+        ));
+        assert!(matches!(
+            command("do GoToWorkspaceIndex 3"),
+            WF::GoToWorkspaceIndex(3)
+        ));
+    }
+
+    #[test]
+    fn json_flag_and_queries() {
+        let cli = parse_words("query apps --json").unwrap();
+        assert!(cli.json);
+        assert!(matches!(
+            cli.invocation,
+            Invocation::Send(Request::Query(Query::Apps))
+        ));
+        assert!(!parse_words("query apps").unwrap().json);
+    }
+
+    #[test]
+    fn subscribe_filters() {
+        assert!(matches!(
+            parse_words("subscribe focus workspace").unwrap().invocation,
+            Invocation::Subscribe(kinds) if kinds == [EventKind::Focus, EventKind::Workspace]
+        ));
+        assert!(matches!(
+            parse_words("subscribe").unwrap().invocation,
+            Invocation::Subscribe(kinds) if kinds.is_empty()
+        ));
+        assert!(parse_words("subscribe typing").is_err());
+    }
+
+    #[test]
+    fn unknown_input_reports_usage() {
+        assert_eq!(parse_words("").unwrap_err(), USAGE);
+        assert!(parse_words("ws sideways").unwrap_err().contains(USAGE));
+    }
+
+    #[test]
+    fn bad_arguments_are_errors() {
+        assert!(parse_words("grid three").is_err());
+        assert!(
+            parse_words("do Fly")
+                .unwrap_err()
+                .contains("unknown variant")
+        );
+    }
+}
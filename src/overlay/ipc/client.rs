@@ -0,0 +1,40 @@
+use interprocess::local_socket::{Stream, prelude::*};
+use std::io::{self, BufRead, BufReader, Lines, Write};
+
+use crate::overlay::{
+    events::EventKind,
+    ipc::{
+        protocol::{Reply, Request},
+        socket_name,
+    },
+};
+
+/// send one request to the manager listening on `name` and wait for its reply
+pub fn request(name: &str, request: &Request) -> io::Result<Reply> {
+    send(name, request).map(|(_, reply)| reply)
+}
+
+/// every event of `kinds` from now on, one JSON object per line
+pub fn subscribe(name: &str, kinds: Vec<EventKind>) -> io::Result<Lines<BufReader<Stream>>> {
+    let (conn, reply) = send(name, &Request::Subscribe(kinds))?;
+    match reply.error {
+        Some(err) => Err(io::Error::other(err)),
+        None => Ok(conn.lines()),
+    }
+}
+
+fn send(name: &str, request: &Request) -> io::Result<(BufReader<Stream>, Reply)> {
+    let mut conn = BufReader::new(Stream::connect(socket_name(name)?)?);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    conn.get_mut().write_all(line.as_bytes())?;
+    let mut reply = String::new();
+    if conn.read_line(&mut reply)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "manager closed the connection",
+        ));
+    }
+    let reply = serde_json::from_str(&reply)?;
+    Ok((conn, reply))
+}
@@ -0,0 +1,61 @@
+pub mod cli;
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+use interprocess::local_socket::{GenericFilePath, GenericNamespaced, Name, prelude::*};
+use std::{io, sync::Arc};
+
+use crate::overlay::{
+    config::NtekConfig,
+    ipc::protocol::{Query, Reply, Request},
+    manager::OverlayManager,
+    overlay_handler::OverlayHandler,
+};
+
+pub const SOCKET_ENV: &str = "TSCK_WINDOW_SOCKET";
+pub const SOCKET_NAME: &str = "tsck-window.sock";
+
+/// `TSCK_WINDOW_SOCKET` or the default, the manager and its clients look it up the same way
+pub fn socket_name_from_env() -> String {
+    std::env::var(SOCKET_ENV).unwrap_or_else(|_| SOCKET_NAME.to_string())
+}
+
+/// named pipe on Windows, abstract unix socket on Linux, a socket file in the
+/// temp dir anywhere else
+fn socket_name(name: &str) -> io::Result<Name<'static>> {
+    if GenericNamespaced::is_supported() {
+        name.to_string().to_ns_name::<GenericNamespaced>()
+    } else {
+        std::env::temp_dir()
+            .join(name)
+            .to_fs_name::<GenericFilePath>()
+    }
+}
+
+//==============================================================================//
+// tag         : DISPATCH
+// description : requests against the running manager
+//==============================================================================//
+/// `Reload` needs to know where the config came from, the binary answers that one
+pub fn dispatch(manager: &Arc<OverlayManager>, config: Arc<NtekConfig>, request: Request) -> Reply {
+    match request {
+        Request::Command(command) => match command.do_stuff(manager.clone(), config) {
+            Ok(()) => Reply::ok(),
+            Err(err) => Reply::error(format!("{err:#}")),
+        },
+        Request::Query(query) => manager.with_handler(|hd| run_query(hd, query)),
+        Request::Reload => Reply::error("reload is not supported here"),
+        Request::Subscribe(_) => Reply::error("subscribe only works on a socket connection"),
+    }
+}
+
+fn run_query(hd: &OverlayHandler, query: Query) -> Reply {
+    match query {
+        Query::Apps => Reply::data(hd.get_all_apps()),
+        Query::Workspaces => Reply::data(hd.get_all_workspaces()),
+        Query::Monitors => Reply::data(&hd.monitors),
+        Query::Focused => Reply::data(hd.get_focused_app()),
+        Query::State => Reply::data(hd.snapshot()),
+    }
+}
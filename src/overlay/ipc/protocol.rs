@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::overlay::{config::WF, events::EventKind};
+
+//==============================================================================//
+// tag         : PROTOCOL
+// description : one JSON object per line each way, a request gets exactly one reply
+//==============================================================================//
+/// `{"command": "ToggleFloat"}`, `{"command": {"GoToWorkspace": "Next"}}`,
+/// `{"query": "apps"}`, `"reload"` or `{"subscribe": ["focus", "workspace"]}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    Command(WF),
+    Query(Query),
+    Reload,
+    /// the connection gets one reply and then an event per line, an empty list
+    /// is every kind of event
+    Subscribe(Vec<EventKind>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Query {
+    Apps,
+    Workspaces,
+    Monitors,
+    Focused,
+    /// everything at once, see `StateSnapshot`
+    State,
+}
+
+/// `{"ok": true, "data": ...}` or `{"ok": false, "error": "..."}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+impl Reply {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            data: None,
+            error: None,
+        }
+    }
+    pub fn data(data: impl Serialize) -> Self {
+        match serde_json::to_value(data) {
+            Ok(data) => Self {
+                data: Some(data),
+                ..Self::ok()
+            },
+            Err(err) => Self::error(err),
+        }
+    }
+    pub fn error(err: impl Display) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::config::{CycleDirection, MonitorTarget};
+
+    #[test]
+    fn requests_read_like_the_config() {
+        let parse = |json: &str| serde_json::from_str::<Request>(json).unwrap();
+        assert!(matches!(
+            parse(r#"{"command": "ToggleFloat"}"#),
+            Request::Command(WF::ToggleFloat)
+        ));
+        assert!(matches!(
+            parse(r#"{"command": {"GoToWorkspace": "Next"}}"#),
+            Request::Command(WF::GoToWorkspace(CycleDirection::Next))
+        ));
+        assert!(matches!(
+            parse(r#"{"command": {"MoveToMonitor": {"Index": 1}}}"#),
+            Request::Command(WF::MoveToMonitor(MonitorTarget::Index(1)))
+        ));
+        assert!(matches!(
+            parse(r#"{"query": "apps"}"#),
+            Request::Query(Query::Apps)
+        ));
+        assert!(matches!(parse(r#""reload""#), Request::Reload));
+        assert!(matches!(
+            parse(r#"{"subscribe": ["title", "config_reload"]}"#),
+            Request::Subscribe(kinds) if kinds == [EventKind::Title, EventKind::ConfigReload]
+        ));
+        assert!(serde_json::from_str::<Request>(r#"{"command": "Fly"}"#).is_err());
+    }
+
+    #[test]
+    fn replies_leave_out_what_is_not_there() {
+        assert_eq!(
+            serde_json::to_string(&Reply::ok()).unwrap(),
+            r#"{"ok":true}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Reply::error("no active app")).unwrap(),
+            r#"{"ok":false,"error":"no active app"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Reply::data(vec![1, 2])).unwrap(),
+            r#"{"ok":true,"data":[1,2]}"#
+        );
+    }
+}
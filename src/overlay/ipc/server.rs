@@ -0,0 +1,150 @@
+use interprocess::local_socket::{ListenerOptions, Stream, prelude::*};
+use serde::Serialize;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    sync::Arc,
+    thread,
+};
+
+use crate::overlay::{
+    events::{self, EventKind},
+    ipc::{
+        protocol::{Reply, Request},
+        socket_name,
+    },
+};
+
+//==============================================================================//
+// tag         : SERVER
+// description : one thread per connection, a connection can send any number
+//               of requests
+//==============================================================================//
+/// start listening on `name`, every request goes through `handle`
+pub fn spawn<F>(name: &str, handle: F) -> io::Result<()>
+where
+    F: Fn(Request) -> Reply + Send + Sync + 'static,
+{
+    let listener = ListenerOptions::new()
+        .name(socket_name(name)?)
+        .create_sync()?;
+    let handle = Arc::new(handle);
+    thread::spawn(move || {
+        for conn in listener.incoming() {
+            match conn {
+                Ok(conn) => {
+                    let handle = handle.clone();
+                    thread::spawn(move || {
+                        if let Err(err) = serve(conn, handle.as_ref()) {
+                            eprintln!("IPC connection error {err}");
+                        }
+                    });
+                }
+                Err(err) => eprintln!("IPC accept error {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn serve(conn: Stream, handle: &dyn Fn(Request) -> Reply) -> io::Result<()> {
+    let mut conn = BufReader::new(conn);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if conn.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Subscribe(kinds)) => return stream_events(conn.into_inner(), kinds),
+            Ok(request) => handle(request),
+            Err(err) => Reply::error(format!("invalid request: {err}")),
+        };
+        write_line(conn.get_mut(), &reply)?;
+    }
+}
+
+/// a subscribed connection only gets events until it hangs up, it has its own
+/// queue so a slow reader never holds up the manager
+fn stream_events(mut conn: Stream, kinds: Vec<EventKind>) -> io::Result<()> {
+    let events = events::bus().subscribe(kinds);
+    write_line(&mut conn, &Reply::ok())?;
+    for event in events.iter() {
+        if write_line(&mut conn, &event).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn write_line(conn: &mut Stream, value: &impl Serialize) -> io::Result<()> {
+    let mut out = serde_json::to_string(value)?;
+    out.push('\n');
+    conn.write_all(out.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::{
+        config::WF,
+        ipc::{client, protocol::Query},
+    };
+
+    #[test]
+    fn requests_get_replies() {
+        let name = format!("tsck-window-test-{}.sock", std::process::id());
+        spawn(&name, |request| match request {
+            Request::Command(WF::ToggleFloat) => Reply::ok(),
+            Request::Query(Query::Focused) => Reply::data("code.exe"),
+            _ => Reply::error("nope"),
+        })
+        .unwrap();
+
+        let send = |request| client::request(&name, &request).unwrap();
+        assert_eq!(send(Request::Command(WF::ToggleFloat)), Reply::ok());
+        assert_eq!(
+            send(Request::Query(Query::Focused)),
+            Reply::data("code.exe")
+        );
+        assert_eq!(send(Request::Reload), Reply::error("nope"));
+
+        // garbage gets an error, the connection stays usable
+        let mut conn = BufReader::new(Stream::connect(socket_name(&name).unwrap()).unwrap());
+        let mut reply = String::new();
+        conn.get_mut()
+            .write_all(b"{\"command\": 5}\n\"reload\"\n")
+            .unwrap();
+        conn.read_line(&mut reply).unwrap();
+        assert!(reply.starts_with(r#"{"ok":false,"error":"invalid request"#));
+        reply.clear();
+        conn.read_line(&mut reply).unwrap();
+        assert_eq!(reply, "{\"ok\":false,\"error\":\"nope\"}\n");
+    }
+
+    #[test]
+    fn subscribers_get_events_as_lines() {
+        let name = format!("tsck-window-events-test-{}.sock", std::process::id());
+        spawn(&name, |_| Reply::ok()).unwrap();
+
+        let mut lines = client::subscribe(&name, vec![EventKind::Monitor]).unwrap();
+        events::publish(EventKind::ConfigReload, || events::Event::ConfigReload {
+            workspaces: vec![],
+        });
+        events::publish(EventKind::Monitor, || events::Event::Monitor {
+            monitor: 4242,
+            workspace: None,
+        });
+        // other tests publish on the same bus, look for ours
+        let line = lines
+            .find(|line| line.as_ref().unwrap().contains("4242"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            line,
+            r#"{"event":"monitor","monitor":4242,"workspace":null}"#
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::overlay::{layout::LayoutRect, workspaces::Hwnd};
+
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 0.9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BspNode {
+    Leaf(Hwnd),
+    Split {
+        // share of `first`
+        ratio: f32,
+        first: Box<BspNode>,
+        second: Box<BspNode>,
+    },
+}
+
+/// binary space partition of one workspace on one monitor.
+/// the split axis is not stored, every split cuts its rect along the longer side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BspTree {
+    root: Option<BspNode>,
+}
+
+impl BspTree {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+    pub fn contains(&self, hwnd: Hwnd) -> bool {
+        self.hwnds().contains(&hwnd)
+    }
+    /// leaves in order, first to last
+    pub fn hwnds(&self) -> Vec<Hwnd> {
+        fn walk(node: &BspNode, out: &mut Vec<Hwnd>) {
+            match node {
+                BspNode::Leaf(h) => out.push(*h),
+                BspNode::Split { first, second, .. } => {
+                    walk(first, out);
+                    walk(second, out);
+                }
+            }
+        }
+        let mut out = vec![];
+        if let Some(root) = &self.root {
+            walk(root, &mut out);
+        }
+        out
+    }
+    /// split the `target` leaf in half and put `hwnd` in the second part.
+    /// without a (known) target the last leaf gets split.
+    pub fn insert(&mut self, hwnd: Hwnd, target: Option<Hwnd>) {
+        if self.contains(hwnd) {
+            return;
+        }
+        if self.root.is_none() {
+            self.root = Some(BspNode::Leaf(hwnd));
+            return;
+        }
+        let Some(root) = self.root.as_mut() else {
+            return;
+        };
+        let target = target
+            .filter(|t| Self::find_leaf(root, *t))
+            .unwrap_or_else(|| Self::last_leaf(root));
+        Self::split_leaf(root, target, hwnd);
+    }
+    /// the sibling of the removed leaf takes over its parent's rect
+    pub fn remove(&mut self, hwnd: Hwnd) -> bool {
+        fn remove_from(node: BspNode, hwnd: Hwnd) -> Option<BspNode> {
+            match node {
+                BspNode::Leaf(h) if h == hwnd => None,
+                BspNode::Leaf(h) => Some(BspNode::Leaf(h)),
+                BspNode::Split {
+                    ratio,
+                    first,
+                    second,
+                } => match (remove_from(*first, hwnd), remove_from(*second, hwnd)) {
+                    (Some(first), Some(second)) => Some(BspNode::Split {
+                        ratio,
+                        first: Box::new(first),
+                        second: Box::new(second),
+                    }),
+                    (Some(node), None) | (None, Some(node)) => Some(node),
+                    (None, None) => None,
+                },
+            }
+        }
+        if !self.contains(hwnd) {
+            return false;
+        }
+        self.root = self.root.take().and_then(|root| remove_from(root, hwnd));
+        true
+    }
+    /// grow (positive delta) or shrink the side `hwnd` sits on in its parent split
+    pub fn resize(&mut self, hwnd: Hwnd, delta: f32) -> bool {
+        fn resize_node(node: &mut BspNode, hwnd: Hwnd, delta: f32) -> bool {
+            let BspNode::Split {
+                ratio,
+                first,
+                second,
+            } = node
+            else {
+                return false;
+            };
+            if **first == BspNode::Leaf(hwnd) {
+                *ratio = (*ratio + delta).clamp(MIN_RATIO, MAX_RATIO);
+                return true;
+            }
+            if **second == BspNode::Leaf(hwnd) {
+                *ratio = (*ratio - delta).clamp(MIN_RATIO, MAX_RATIO);
+                return true;
+            }
+            resize_node(first, hwnd, delta) || resize_node(second, hwnd, delta)
+        }
+        match self.root.as_mut() {
+            Some(root) => resize_node(root, hwnd, delta),
+            None => false,
+        }
+    }
+    /// `a` takes the leaf of `b` and the other way around, either may be missing
+    /// from this tree when the windows live on different monitors
+    pub fn swap(&mut self, a: Hwnd, b: Hwnd) -> bool {
+        fn swap_node(node: &mut BspNode, a: Hwnd, b: Hwnd) -> bool {
+            match node {
+                BspNode::Leaf(h) if *h == a => {
+                    *h = b;
+                    true
+                }
+                BspNode::Leaf(h) if *h == b => {
+                    *h = a;
+                    true
+                }
+                BspNode::Leaf(_) => false,
+                BspNode::Split { first, second, .. } => {
+                    // no short circuit, both leaves have to change
+                    swap_node(first, a, b) | swap_node(second, a, b)
+                }
+            }
+        }
+        match self.root.as_mut() {
+            Some(root) => swap_node(root, a, b),
+            None => false,
+        }
+    }
+    /// drop leaves that are gone and append the missing ones, keeping existing splits
+    pub fn sync(&mut self, hwnds: &[Hwnd]) {
+        for gone in self.hwnds().into_iter().filter(|h| !hwnds.contains(h)) {
+            self.remove(gone);
+        }
+        for hwnd in hwnds {
+            self.insert(*hwnd, None);
+        }
+    }
+    pub fn layout(&self, area: LayoutRect) -> Vec<(Hwnd, LayoutRect)> {
+        fn walk(node: &BspNode, area: LayoutRect, out: &mut Vec<(Hwnd, LayoutRect)>) {
+            match node {
+                BspNode::Leaf(h) => out.push((*h, area)),
+                BspNode::Split {
+                    ratio,
+                    first,
+                    second,
+                } => {
+                    let (a, b) = if area.width >= area.height {
+                        area.split_vertical(*ratio)
+                    } else {
+                        area.split_horizontal(*ratio)
+                    };
+                    walk(first, a, out);
+                    walk(second, b, out);
+                }
+            }
+        }
+        let mut out = vec![];
+        if let Some(root) = &self.root {
+            walk(root, area, &mut out);
+        }
+        out
+    }
+
+    fn find_leaf(node: &BspNode, hwnd: Hwnd) -> bool {
+        match node {
+            BspNode::Leaf(h) => *h == hwnd,
+            BspNode::Split { first, second, .. } => {
+                Self::find_leaf(first, hwnd) || Self::find_leaf(second, hwnd)
+            }
+        }
+    }
+    fn last_leaf(node: &BspNode) -> Hwnd {
+        match node {
+            BspNode::Leaf(h) => *h,
+            BspNode::Split { second, .. } => Self::last_leaf(second),
+        }
+    }
+    fn split_leaf(node: &mut BspNode, target: Hwnd, hwnd: Hwnd) -> bool {
+        match node {
+            BspNode::Leaf(h) if *h == target => {
+                *node = BspNode::Split {
+                    ratio: 0.5,
+                    first: Box::new(BspNode::Leaf(target)),
+                    second: Box::new(BspNode::Leaf(hwnd)),
+                };
+                true
+            }
+            BspNode::Leaf(_) => false,
+            BspNode::Split { first, second, .. } => {
+                Self::split_leaf(first, target, hwnd) || Self::split_leaf(second, target, hwnd)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: LayoutRect = LayoutRect {
+        x: 0,
+        y: 30,
+        width: 1920,
+        height: 1050,
+    };
+
+    #[test]
+    fn single_window_fills_area() {
+        let mut tree = BspTree::new();
+        tree.insert(1, None);
+        assert_eq!(tree.layout(AREA), vec![(1, AREA)]);
+    }
+
+    #[test]
+    fn splits_along_longer_axis() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2, 3]);
+        assert_eq!(
+            tree.layout(AREA),
+            vec![
+                (1, LayoutRect::new(0, 30, 960, 1050)),
+                (2, LayoutRect::new(960, 30, 960, 525)),
+                (3, LayoutRect::new(960, 555, 960, 525)),
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_splits_target() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2]);
+        tree.insert(3, Some(1));
+        assert_eq!(tree.hwnds(), vec![1, 3, 2]);
+        assert_eq!(tree.layout(AREA)[1], (3, LayoutRect::new(0, 555, 960, 525)));
+    }
+
+    #[test]
+    fn remove_gives_space_to_sibling() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2, 3]);
+        assert!(tree.remove(2));
+        assert!(!tree.remove(2));
+        assert_eq!(
+            tree.layout(AREA),
+            vec![
+                (1, LayoutRect::new(0, 30, 960, 1050)),
+                (3, LayoutRect::new(960, 30, 960, 1050)),
+            ]
+        );
+        tree.sync(&[]);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn resize_is_clamped() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2]);
+        assert!(tree.resize(2, 0.25));
+        assert_eq!(tree.layout(AREA)[0].1.width, 480);
+        tree.resize(1, 5.0);
+        assert_eq!(tree.layout(AREA)[0].1.width, 1728);
+        assert!(!tree.resize(9, 0.1));
+    }
+
+    #[test]
+    fn swap_exchanges_leaves() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2, 3]);
+        assert!(tree.swap(1, 3));
+        assert_eq!(tree.layout(AREA)[0], (3, LayoutRect::new(0, 30, 960, 1050)));
+        assert_eq!(tree.hwnds(), vec![3, 2, 1]);
+        assert!(tree.swap(2, 9));
+        assert_eq!(tree.hwnds(), vec![3, 9, 1]);
+        assert!(!tree.swap(7, 8));
+    }
+}
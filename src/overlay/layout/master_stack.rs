@@ -0,0 +1,134 @@
+use crate::overlay::{layout::LayoutRect, workspaces::Hwnd};
+
+pub const MASTER_RATIO_STEP: f32 = 0.05;
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 0.9;
+
+/// dwm style: `master_count` windows stacked on the left taking `master_ratio`
+/// of the width, everything else stacked on the right
+#[derive(Debug, Clone, PartialEq)]
+pub struct MasterStack {
+    pub master_count: usize,
+    pub master_ratio: f32,
+}
+
+impl Default for MasterStack {
+    fn default() -> Self {
+        Self {
+            master_count: 1,
+            master_ratio: 0.55,
+        }
+    }
+}
+
+impl MasterStack {
+    pub fn new(master_count: usize, master_ratio: f32) -> Self {
+        Self {
+            master_count,
+            master_ratio: master_ratio.clamp(MIN_RATIO, MAX_RATIO),
+        }
+    }
+    pub fn increase_master_count(&mut self) {
+        self.master_count += 1;
+    }
+    pub fn decrease_master_count(&mut self) {
+        self.master_count = self.master_count.saturating_sub(1);
+    }
+    pub fn resize_master(&mut self, delta: f32) {
+        self.master_ratio = (self.master_ratio + delta).clamp(MIN_RATIO, MAX_RATIO);
+    }
+    pub fn layout(&self, area: LayoutRect, hwnds: &[Hwnd]) -> Vec<(Hwnd, LayoutRect)> {
+        let masters = self.master_count.min(hwnds.len());
+        let stacked = hwnds.len() - masters;
+        let rects = if masters == 0 || stacked == 0 {
+            area.rows(hwnds.len())
+        } else {
+            let (master, stack) = area.split_vertical(self.master_ratio);
+            let mut rects = master.rows(masters);
+            rects.extend(stack.rows(stacked));
+            rects
+        };
+        hwnds.iter().copied().zip(rects).collect()
+    }
+}
+
+/// move the matching item to the front, when it already is first it swaps with the second
+pub fn promote<T>(items: &mut Vec<T>, is_target: impl Fn(&T) -> bool) -> bool {
+    let Some(index) = items.iter().position(is_target) else {
+        return false;
+    };
+    if index == 0 {
+        if items.len() > 1 {
+            items.swap(0, 1);
+        }
+        return true;
+    }
+    let item = items.remove(index);
+    items.insert(0, item);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: LayoutRect = LayoutRect {
+        x: 0,
+        y: 30,
+        width: 2000,
+        height: 1050,
+    };
+
+    #[test]
+    fn single_window_fills_area() {
+        let layout = MasterStack::default();
+        assert_eq!(layout.layout(AREA, &[1]), vec![(1, AREA)]);
+    }
+
+    #[test]
+    fn master_and_stack() {
+        let layout = MasterStack::new(1, 0.6);
+        assert_eq!(
+            layout.layout(AREA, &[1, 2, 3]),
+            vec![
+                (1, LayoutRect::new(0, 30, 1200, 1050)),
+                (2, LayoutRect::new(1200, 30, 800, 525)),
+                (3, LayoutRect::new(1200, 555, 800, 525)),
+            ]
+        );
+    }
+
+    #[test]
+    fn master_count_bounds() {
+        let mut layout = MasterStack::new(1, 0.5);
+        layout.increase_master_count();
+        let rects = layout.layout(AREA, &[1, 2, 3]);
+        assert_eq!(rects[1], (2, LayoutRect::new(0, 555, 1000, 525)));
+        assert_eq!(rects[2], (3, LayoutRect::new(1000, 30, 1000, 1050)));
+
+        layout.decrease_master_count();
+        layout.decrease_master_count();
+        layout.decrease_master_count();
+        assert_eq!(layout.master_count, 0);
+        assert_eq!(layout.layout(AREA, &[1, 2])[0].1.width, 2000);
+    }
+
+    #[test]
+    fn resize_is_clamped() {
+        let mut layout = MasterStack::new(1, 0.5);
+        layout.resize_master(MASTER_RATIO_STEP);
+        assert_eq!(layout.layout(AREA, &[1, 2])[0].1.width, 1100);
+        layout.resize_master(-5.0);
+        assert_eq!(layout.master_ratio, MIN_RATIO);
+    }
+
+    #[test]
+    fn promote_swaps_master() {
+        let mut hwnds = vec![1, 2, 3];
+        assert!(promote(&mut hwnds, |h| *h == 3));
+        assert_eq!(hwnds, vec![3, 1, 2]);
+        assert!(promote(&mut hwnds, |h| *h == 3));
+        assert_eq!(hwnds, vec![1, 3, 2]);
+        assert!(!promote(&mut hwnds, |h| *h == 9));
+    }
+}
@@ -0,0 +1,306 @@
+pub mod bsp;
+pub mod master_stack;
+pub mod monocle;
+pub mod neighbour;
+pub mod scrolling;
+
+use crate::overlay::{app_info::AppInfo, monitor_info::StatusbarMonitorInfo};
+use std::collections::HashMap;
+
+/// y position for windows that should not be seen, same as workspace parking
+pub const PARKED_Y: i32 = -2000;
+/// where `reorder_app_pos_in_workspace` parks windows of hidden workspaces
+pub const PARKED_BELOW_Y: i32 = 2000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LayoutRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+impl LayoutRect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+    /// `ratio` of the width and height, in the middle of this rect
+    pub fn centered(&self, ratio: f32) -> LayoutRect {
+        let width = (self.width as f32 * ratio) as i32;
+        let height = (self.height as f32 * ratio) as i32;
+        LayoutRect::new(
+            self.x + (self.width - width) / 2,
+            self.y + (self.height - height) / 2,
+            width,
+            height,
+        )
+    }
+    pub fn intersects(&self, other: &LayoutRect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+    /// split side by side, `ratio` is the share of the left part
+    pub fn split_vertical(&self, ratio: f32) -> (LayoutRect, LayoutRect) {
+        let left = (self.width as f32 * ratio) as i32;
+        (
+            LayoutRect::new(self.x, self.y, left, self.height),
+            LayoutRect::new(self.x + left, self.y, self.width - left, self.height),
+        )
+    }
+    /// `count` equal rows top to bottom, the last one takes the rounding leftover
+    pub fn rows(&self, count: usize) -> Vec<LayoutRect> {
+        if count == 0 {
+            return vec![];
+        }
+        let height = self.height / count as i32;
+        (0..count)
+            .map(|i| {
+                let y = self.y + height * i as i32;
+                let h = if i == count - 1 {
+                    self.bottom() - y
+                } else {
+                    height
+                };
+                LayoutRect::new(self.x, y, self.width, h)
+            })
+            .collect()
+    }
+    /// split top and bottom, `ratio` is the share of the top part
+    pub fn split_horizontal(&self, ratio: f32) -> (LayoutRect, LayoutRect) {
+        let top = (self.height as f32 * ratio) as i32;
+        (
+            LayoutRect::new(self.x, self.y, self.width, top),
+            LayoutRect::new(self.x, self.y + top, self.width, self.height - top),
+        )
+    }
+}
+impl From<&AppInfo> for LayoutRect {
+    fn from(app: &AppInfo) -> Self {
+        LayoutRect::new(
+            app.position.x,
+            app.position.y,
+            app.size.width,
+            app.size.height,
+        )
+    }
+}
+impl From<&StatusbarMonitorInfo> for LayoutRect {
+    fn from(monitor: &StatusbarMonitorInfo) -> Self {
+        LayoutRect::new(monitor.x, monitor.y, monitor.width, monitor.height)
+    }
+}
+impl std::fmt::Display for LayoutRect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({}, {}, {}, {})",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+/// monitor rect minus the statusbar on top
+pub fn usable_area(monitor: &StatusbarMonitorInfo, statusbar_height: i32) -> LayoutRect {
+    LayoutRect::new(
+        monitor.x,
+        monitor.y + statusbar_height,
+        monitor.width,
+        monitor.height - statusbar_height,
+    )
+}
+
+/// `rect` moved from `from` to `to` keeping its relative place and size
+pub fn scale_rect(rect: LayoutRect, from: LayoutRect, to: LayoutRect) -> LayoutRect {
+    let sx = to.width as f32 / from.width.max(1) as f32;
+    let sy = to.height as f32 / from.height.max(1) as f32;
+    let width = ((rect.width as f32 * sx).round() as i32).min(to.width);
+    let height = ((rect.height as f32 * sy).round() as i32).min(to.height);
+    let x = to.x + ((rect.x - from.x) as f32 * sx).round() as i32;
+    let y = to.y + ((rect.y - from.y) as f32 * sy).round() as i32;
+    LayoutRect::new(
+        x.clamp(to.x, to.right() - width),
+        y.clamp(to.y, to.bottom() - height),
+        width,
+        height,
+    )
+}
+
+//==============================================================================//
+// tag         : GAPS
+// description : space around the usable area (outer) and between windows (inner)
+//==============================================================================//
+/// how much one `IncreaseGaps` / `DecreaseGaps` changes both gaps
+pub const GAP_STEP: i32 = 4;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Gaps {
+    pub outer: i32,
+    pub inner: i32,
+}
+impl Gaps {
+    pub fn new(outer: i32, inner: i32) -> Self {
+        Self { outer, inner }
+    }
+    /// `area` with the outer gap taken off every side
+    pub fn shrink(&self, area: LayoutRect) -> LayoutRect {
+        LayoutRect::new(
+            area.x + self.outer,
+            area.y + self.outer,
+            (area.width - self.outer * 2).max(1),
+            (area.height - self.outer * 2).max(1),
+        )
+    }
+    /// pull `rect` in by half the inner gap on every side that has a neighbour,
+    /// sides lying on the edge of `area` stay where they are
+    pub fn inset(&self, rect: LayoutRect, area: LayoutRect) -> LayoutRect {
+        let half = self.inner / 2;
+        let rest = self.inner - half;
+        let left = if rect.x > area.x { half } else { 0 };
+        let top = if rect.y > area.y { half } else { 0 };
+        let right = if rect.right() < area.right() { rest } else { 0 };
+        let bottom = if rect.bottom() < area.bottom() {
+            rest
+        } else {
+            0
+        };
+        LayoutRect::new(
+            rect.x + left,
+            rect.y + top,
+            (rect.width - left - right).max(1),
+            (rect.height - top - bottom).max(1),
+        )
+    }
+}
+
+/// configured gaps plus the runtime adjustments from the gap hotkeys
+#[derive(Debug, Default, Clone)]
+pub struct GapSettings {
+    pub base: Gaps,
+    pub monitors: HashMap<usize, Gaps>,
+    pub workspaces: HashMap<String, Gaps>,
+    pub offset: i32,
+    pub disabled: bool,
+}
+impl GapSettings {
+    /// workspace override wins over monitor override, which wins over the base
+    pub fn resolve(&self, monitor: usize, workspace: &str) -> Gaps {
+        if self.disabled {
+            return Gaps::default();
+        }
+        let gaps = self
+            .workspaces
+            .get(workspace)
+            .or_else(|| self.monitors.get(&monitor))
+            .unwrap_or(&self.base);
+        Gaps::new(
+            (gaps.outer + self.offset).max(0),
+            (gaps.inner + self.offset).max(0),
+        )
+    }
+    /// grow or shrink every gap, never below what makes all of them zero
+    pub fn adjust(&mut self, delta: i32) {
+        let largest = std::iter::once(&self.base)
+            .chain(self.monitors.values())
+            .chain(self.workspaces.values())
+            .map(|g| g.outer.max(g.inner))
+            .max()
+            .unwrap_or(0);
+        self.offset = (self.offset + delta).max(-largest);
+        self.disabled = false;
+    }
+    pub fn toggle(&mut self) {
+        self.disabled = !self.disabled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrink_takes_outer_gap_off_every_side() {
+        let area = LayoutRect::new(0, 30, 1920, 1050);
+        assert_eq!(
+            Gaps::new(10, 0).shrink(area),
+            LayoutRect::new(10, 40, 1900, 1030)
+        );
+    }
+
+    #[test]
+    fn inset_only_touches_inner_edges() {
+        let area = LayoutRect::new(0, 0, 1000, 500);
+        let (left, right) = area.split_vertical(0.5);
+        let gaps = Gaps::new(0, 10);
+        assert_eq!(gaps.inset(left, area), LayoutRect::new(0, 0, 495, 500));
+        assert_eq!(gaps.inset(right, area), LayoutRect::new(505, 0, 495, 500));
+        assert_eq!(gaps.inset(area, area), area);
+    }
+
+    #[test]
+    fn centered_keeps_the_middle() {
+        let area = LayoutRect::new(0, 30, 1920, 1050);
+        assert_eq!(area.centered(0.6), LayoutRect::new(384, 240, 1152, 630));
+        assert_eq!(area.centered(1.0), area);
+    }
+
+    #[test]
+    fn scale_rect_between_monitors() {
+        let from = LayoutRect::new(0, 30, 1920, 1050);
+        let to = LayoutRect::new(1920, 0, 2560, 1440);
+        assert_eq!(
+            scale_rect(LayoutRect::new(960, 555, 960, 525), from, to),
+            LayoutRect::new(3200, 720, 1280, 720)
+        );
+        // hanging off the source monitor still lands inside the target
+        assert_eq!(
+            scale_rect(LayoutRect::new(1800, 30, 960, 1050), from, to),
+            LayoutRect::new(3200, 0, 1280, 1440)
+        );
+    }
+
+    #[test]
+    fn resolve_prefers_workspace_then_monitor() {
+        let mut settings = GapSettings {
+            base: Gaps::new(8, 8),
+            ..Default::default()
+        };
+        settings.monitors.insert(1, Gaps::new(0, 4));
+        settings.workspaces.insert("Code".into(), Gaps::new(20, 12));
+        assert_eq!(settings.resolve(0, "Work"), Gaps::new(8, 8));
+        assert_eq!(settings.resolve(1, "Work"), Gaps::new(0, 4));
+        assert_eq!(settings.resolve(1, "Code"), Gaps::new(20, 12));
+    }
+
+    #[test]
+    fn adjust_and_toggle() {
+        let mut settings = GapSettings {
+            base: Gaps::new(8, 4),
+            ..Default::default()
+        };
+        settings.adjust(GAP_STEP);
+        assert_eq!(settings.resolve(0, ""), Gaps::new(12, 8));
+        for _ in 0..10 {
+            settings.adjust(-GAP_STEP);
+        }
+        assert_eq!(settings.resolve(0, ""), Gaps::new(0, 0));
+        settings.adjust(GAP_STEP);
+        assert_eq!(settings.resolve(0, ""), Gaps::new(4, 0));
+        settings.toggle();
+        assert_eq!(settings.resolve(0, ""), Gaps::default());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::overlay::{
+    config::CycleDirection,
+    layout::{LayoutRect, PARKED_Y},
+    workspaces::Hwnd,
+};
+
+/// every window takes the whole area, only the focused one stays on screen
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Monocle {
+    pub focused: Option<Hwnd>,
+}
+
+impl Monocle {
+    pub fn focus(&mut self, hwnd: Hwnd) {
+        self.focused = Some(hwnd);
+    }
+    /// falls back to the first window when the focused one is gone
+    pub fn focused_index(&self, hwnds: &[Hwnd]) -> Option<usize> {
+        if hwnds.is_empty() {
+            return None;
+        }
+        Some(
+            self.focused
+                .and_then(|f| hwnds.iter().position(|h| *h == f))
+                .unwrap_or(0),
+        )
+    }
+    pub fn cycle(&mut self, hwnds: &[Hwnd], direction: &CycleDirection) -> Option<Hwnd> {
+        let current = self.focused_index(hwnds)?;
+        let next = hwnds[direction.cycle(current, hwnds.len())];
+        self.focused = Some(next);
+        Some(next)
+    }
+    /// 1 based position of the focused window and the stack size
+    pub fn position(&self, hwnds: &[Hwnd]) -> Option<(usize, usize)> {
+        self.focused_index(hwnds).map(|i| (i + 1, hwnds.len()))
+    }
+    pub fn layout(&self, area: LayoutRect, hwnds: &[Hwnd]) -> Vec<(Hwnd, LayoutRect)> {
+        let focused = self.focused_index(hwnds);
+        hwnds
+            .iter()
+            .enumerate()
+            .map(|(i, hwnd)| {
+                let y = if Some(i) == focused { area.y } else { PARKED_Y };
+                (*hwnd, LayoutRect { y, ..area })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: LayoutRect = LayoutRect {
+        x: 0,
+        y: 30,
+        width: 1920,
+        height: 1050,
+    };
+
+    #[test]
+    fn only_focused_is_visible() {
+        let mut monocle = Monocle::default();
+        monocle.focus(2);
+        assert_eq!(
+            monocle.layout(AREA, &[1, 2]),
+            vec![
+                (
+                    1,
+                    LayoutRect {
+                        y: PARKED_Y,
+                        ..AREA
+                    }
+                ),
+                (2, AREA)
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle_wraps_and_reports_position() {
+        let mut monocle = Monocle::default();
+        let hwnds = [1, 2, 3];
+        assert_eq!(monocle.position(&hwnds), Some((1, 3)));
+        assert_eq!(monocle.cycle(&hwnds, &CycleDirection::Prev), Some(3));
+        assert_eq!(monocle.position(&hwnds), Some((3, 3)));
+        assert_eq!(monocle.cycle(&hwnds, &CycleDirection::Next), Some(1));
+        // focused window closed
+        assert_eq!(monocle.position(&[2, 3]), Some((1, 2)));
+        assert_eq!(monocle.cycle(&[], &CycleDirection::Next), None);
+    }
+}
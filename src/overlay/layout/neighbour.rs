@@ -0,0 +1,127 @@
+use crate::overlay::{config::Direction, layout::LayoutRect};
+
+/// how good `to` is as the next rect from `from` going `direction`, lower wins.
+/// `to` has to start past the edge `from` starts at and have its centre further
+/// that way. rects sharing a row/column with `from` beat the ones that only lie
+/// diagonally, then the closest centre along the axis, then the smallest drift
+/// across it.
+fn score(from: LayoutRect, to: LayoutRect, direction: &Direction) -> Option<(bool, i32, i32)> {
+    let (fx, fy) = from.center();
+    let (tx, ty) = to.center();
+    let (along, past_edge) = match direction {
+        Direction::Left => (fx - tx, to.right() < from.right()),
+        Direction::Right => (tx - fx, to.x > from.x),
+        Direction::Up => (fy - ty, to.bottom() < from.bottom()),
+        Direction::Down => (ty - fy, to.y > from.y),
+    };
+    if along <= 0 || !past_edge {
+        return None;
+    }
+    let (overlap, across) = match direction {
+        Direction::Left | Direction::Right => (
+            from.y < to.bottom() && to.y < from.bottom(),
+            (fy - ty).abs(),
+        ),
+        Direction::Up | Direction::Down => {
+            (from.x < to.right() && to.x < from.right(), (fx - tx).abs())
+        }
+    };
+    Some((!overlap, along, across))
+}
+
+/// the candidate nearest to `from` in `direction`, `None` when nothing lies that way
+pub fn nearest<T>(
+    from: LayoutRect,
+    candidates: impl IntoIterator<Item = (T, LayoutRect)>,
+    direction: &Direction,
+) -> Option<T> {
+    candidates
+        .into_iter()
+        .filter_map(|(item, rect)| score(from, rect, direction).map(|s| (s, item)))
+        .min_by_key(|(s, _)| *s)
+        .map(|(_, item)| item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1 | 2
+    // --+--
+    // 3 | 4
+    fn grid() -> Vec<(usize, LayoutRect)> {
+        vec![
+            (1, LayoutRect::new(0, 0, 960, 540)),
+            (2, LayoutRect::new(960, 0, 960, 540)),
+            (3, LayoutRect::new(0, 540, 960, 540)),
+            (4, LayoutRect::new(960, 540, 960, 540)),
+        ]
+    }
+    fn from(rects: &[(usize, LayoutRect)], id: usize) -> LayoutRect {
+        rects.iter().find(|(i, _)| *i == id).unwrap().1
+    }
+    fn others(rects: &[(usize, LayoutRect)], id: usize) -> Vec<(usize, LayoutRect)> {
+        rects.iter().copied().filter(|(i, _)| *i != id).collect()
+    }
+
+    #[test]
+    fn moves_through_a_grid() {
+        let rects = grid();
+        let go = |id, direction| nearest(from(&rects, id), others(&rects, id), &direction);
+        assert_eq!(go(1, Direction::Right), Some(2));
+        assert_eq!(go(1, Direction::Down), Some(3));
+        assert_eq!(go(4, Direction::Left), Some(3));
+        assert_eq!(go(4, Direction::Up), Some(2));
+        assert_eq!(go(1, Direction::Left), None);
+        assert_eq!(go(1, Direction::Up), None);
+    }
+
+    #[test]
+    fn prefers_rects_in_the_same_row() {
+        // tall master on the left, two stacked on the right
+        let rects = vec![
+            (1, LayoutRect::new(0, 0, 1056, 1080)),
+            (2, LayoutRect::new(1056, 0, 864, 540)),
+            (3, LayoutRect::new(1056, 540, 864, 540)),
+        ];
+        assert_eq!(
+            nearest(from(&rects, 3), others(&rects, 3), &Direction::Left),
+            Some(1)
+        );
+        assert_eq!(
+            nearest(from(&rects, 1), others(&rects, 1), &Direction::Right),
+            Some(2)
+        );
+        // a closer rect that only sits diagonally loses to one straight ahead
+        let rects = vec![
+            (1, LayoutRect::new(0, 0, 400, 400)),
+            (2, LayoutRect::new(500, 500, 400, 400)),
+            (3, LayoutRect::new(1200, 0, 400, 400)),
+        ];
+        assert_eq!(
+            nearest(from(&rects, 1), others(&rects, 1), &Direction::Right),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn works_across_monitors() {
+        let monitors = vec![
+            (0, LayoutRect::new(0, 0, 1920, 1080)),
+            (1, LayoutRect::new(1920, 0, 2560, 1440)),
+            (2, LayoutRect::new(-1920, 0, 1920, 1080)),
+        ];
+        assert_eq!(
+            nearest(from(&monitors, 0), others(&monitors, 0), &Direction::Right),
+            Some(1)
+        );
+        assert_eq!(
+            nearest(from(&monitors, 0), others(&monitors, 0), &Direction::Left),
+            Some(2)
+        );
+        assert_eq!(
+            nearest(from(&monitors, 0), others(&monitors, 0), &Direction::Down),
+            None
+        );
+    }
+}
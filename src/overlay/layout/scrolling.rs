@@ -0,0 +1,331 @@
+use crate::overlay::{
+    config::CycleDirection,
+    layout::{LayoutRect, PARKED_Y},
+    workspaces::Hwnd,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollColumn {
+    pub hwnds: Vec<Hwnd>,
+    // fraction of the monitor width
+    pub width: f32,
+    // focused window inside the column
+    pub row: usize,
+}
+impl ScrollColumn {
+    pub fn new(hwnd: Hwnd, width: f32) -> Self {
+        Self {
+            hwnds: vec![hwnd],
+            width,
+            row: 0,
+        }
+    }
+}
+
+/// niri style infinite strip of columns for one workspace on one monitor.
+/// the viewport follows the focused column, columns outside of it get parked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollingStrip {
+    pub columns: Vec<ScrollColumn>,
+    pub focused: usize,
+    // viewport scroll in px from the first column
+    pub offset: i32,
+    pub column_width: f32,
+}
+
+impl Default for ScrollingStrip {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl ScrollingStrip {
+    pub fn new(column_width: f32) -> Self {
+        Self {
+            columns: vec![],
+            focused: 0,
+            offset: 0,
+            column_width,
+        }
+    }
+    pub fn contains(&self, hwnd: Hwnd) -> bool {
+        self.column_of(hwnd).is_some()
+    }
+    pub fn column_of(&self, hwnd: Hwnd) -> Option<usize> {
+        self.columns.iter().position(|c| c.hwnds.contains(&hwnd))
+    }
+    pub fn focused_hwnd(&self) -> Option<Hwnd> {
+        let column = self.columns.get(self.focused)?;
+        column.hwnds.get(column.row).copied()
+    }
+    /// new column right after the focused one
+    pub fn insert(&mut self, hwnd: Hwnd) {
+        if self.contains(hwnd) {
+            return;
+        }
+        let at = if self.columns.is_empty() {
+            0
+        } else {
+            self.focused + 1
+        };
+        self.columns
+            .insert(at, ScrollColumn::new(hwnd, self.column_width));
+        self.focused = at;
+    }
+    pub fn remove(&mut self, hwnd: Hwnd) -> bool {
+        if !self.contains(hwnd) {
+            return false;
+        }
+        for column in self.columns.iter_mut() {
+            column.hwnds.retain(|h| *h != hwnd);
+        }
+        self.columns.retain(|c| !c.hwnds.is_empty());
+        self.clamp_focus();
+        true
+    }
+    pub fn sync(&mut self, hwnds: &[Hwnd]) {
+        for column in self.columns.iter_mut() {
+            column.hwnds.retain(|h| hwnds.contains(h));
+        }
+        self.columns.retain(|c| !c.hwnds.is_empty());
+        self.clamp_focus();
+        for hwnd in hwnds {
+            self.insert(*hwnd);
+        }
+    }
+    /// `a` takes the place of `b` and the other way around
+    pub fn swap(&mut self, a: Hwnd, b: Hwnd) -> bool {
+        let mut swapped = false;
+        for h in self.columns.iter_mut().flat_map(|c| c.hwnds.iter_mut()) {
+            if *h == a {
+                *h = b;
+                swapped = true;
+            } else if *h == b {
+                *h = a;
+                swapped = true;
+            }
+        }
+        swapped
+    }
+    pub fn focus_hwnd(&mut self, hwnd: Hwnd) -> bool {
+        let Some(index) = self.column_of(hwnd) else {
+            return false;
+        };
+        let column = &mut self.columns[index];
+        column.row = column.hwnds.iter().position(|h| *h == hwnd).unwrap_or(0);
+        self.focused = index;
+        true
+    }
+    pub fn focus_column(&mut self, direction: &CycleDirection) -> bool {
+        let Some(to) = self.neighbour(direction) else {
+            return false;
+        };
+        self.focused = to;
+        true
+    }
+    pub fn move_column(&mut self, direction: &CycleDirection) -> bool {
+        let Some(to) = self.neighbour(direction) else {
+            return false;
+        };
+        self.columns.swap(self.focused, to);
+        self.focused = to;
+        true
+    }
+    /// move the focused window into the neighbouring column
+    pub fn consume(&mut self, direction: &CycleDirection) -> bool {
+        let (Some(hwnd), Some(to)) = (self.focused_hwnd(), self.neighbour(direction)) else {
+            return false;
+        };
+        self.columns[self.focused].hwnds.retain(|h| *h != hwnd);
+        self.columns[to].hwnds.push(hwnd);
+        self.columns.retain(|c| !c.hwnds.is_empty());
+        self.clamp_focus();
+        self.focus_hwnd(hwnd)
+    }
+    /// move the focused window out of its column into a new one on the right
+    pub fn expel(&mut self) -> bool {
+        let Some(hwnd) = self.focused_hwnd() else {
+            return false;
+        };
+        let from = self.focused;
+        if self.columns[from].hwnds.len() < 2 {
+            return false;
+        }
+        self.columns[from].hwnds.retain(|h| *h != hwnd);
+        let width = self.columns[from].width;
+        self.columns
+            .insert(from + 1, ScrollColumn::new(hwnd, width));
+        self.focused = from + 1;
+        self.clamp_focus();
+        true
+    }
+    /// step the focused column width through `size_factor`
+    pub fn cycle_width(&mut self, direction: &CycleDirection, size_factor: &[f32]) -> bool {
+        let Some(column) = self.columns.get_mut(self.focused) else {
+            return false;
+        };
+        if size_factor.is_empty() {
+            return false;
+        }
+        let count = size_factor.len();
+        let current = size_factor
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (*a - column.width)
+                    .abs()
+                    .total_cmp(&(*b - column.width).abs())
+            })
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let next = match direction {
+            CycleDirection::Prev => (current + count - 1) % count,
+            CycleDirection::Next => (current + 1) % count,
+        };
+        column.width = size_factor[next];
+        true
+    }
+    /// scrolls the viewport to the focused column, then returns the rects.
+    /// columns not fully inside the viewport are parked at `PARKED_Y`.
+    pub fn layout(&mut self, area: LayoutRect) -> Vec<(Hwnd, LayoutRect)> {
+        let widths = self
+            .columns
+            .iter()
+            .map(|c| (area.width as f32 * c.width) as i32)
+            .collect::<Vec<_>>();
+        let starts = widths
+            .iter()
+            .scan(0, |x, w| {
+                let start = *x;
+                *x += w;
+                Some(start)
+            })
+            .collect::<Vec<_>>();
+        if let (Some(start), Some(width)) = (starts.get(self.focused), widths.get(self.focused)) {
+            if *start < self.offset {
+                self.offset = *start;
+            } else if start + width > self.offset + area.width {
+                self.offset = start + width - area.width;
+            }
+        }
+        let mut out = vec![];
+        for ((column, start), width) in self.columns.iter().zip(starts).zip(widths) {
+            let visible = start >= self.offset && start + width <= self.offset + area.width;
+            let y = if visible { area.y } else { PARKED_Y };
+            let rect = LayoutRect::new(area.x + start - self.offset, y, width, area.height);
+            out.extend(
+                column
+                    .hwnds
+                    .iter()
+                    .copied()
+                    .zip(rect.rows(column.hwnds.len())),
+            );
+        }
+        out
+    }
+
+    fn neighbour(&self, direction: &CycleDirection) -> Option<usize> {
+        match direction {
+            CycleDirection::Prev => self.focused.checked_sub(1),
+            CycleDirection::Next => {
+                (self.focused + 1 < self.columns.len()).then_some(self.focused + 1)
+            }
+        }
+    }
+    fn clamp_focus(&mut self) {
+        self.focused = self.focused.min(self.columns.len().saturating_sub(1));
+        for column in self.columns.iter_mut() {
+            column.row = column.row.min(column.hwnds.len().saturating_sub(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: LayoutRect = LayoutRect {
+        x: 0,
+        y: 30,
+        width: 2000,
+        height: 1000,
+    };
+
+    #[test]
+    fn viewport_follows_focus() {
+        let mut strip = ScrollingStrip::new(0.5);
+        strip.sync(&[1, 2, 3]);
+        assert_eq!(strip.focused_hwnd(), Some(3));
+        assert_eq!(
+            strip.layout(AREA),
+            vec![
+                (1, LayoutRect::new(-1000, PARKED_Y, 1000, 1000)),
+                (2, LayoutRect::new(0, 30, 1000, 1000)),
+                (3, LayoutRect::new(1000, 30, 1000, 1000)),
+            ]
+        );
+        assert!(strip.focus_column(&CycleDirection::Prev));
+        assert!(strip.focus_column(&CycleDirection::Prev));
+        assert!(!strip.focus_column(&CycleDirection::Prev));
+        let rects = strip.layout(AREA);
+        assert_eq!(strip.offset, 0);
+        assert_eq!(rects[0], (1, LayoutRect::new(0, 30, 1000, 1000)));
+        assert_eq!(rects[2].1.y, PARKED_Y);
+    }
+
+    #[test]
+    fn move_column_keeps_focus() {
+        let mut strip = ScrollingStrip::new(0.5);
+        strip.sync(&[1, 2, 3]);
+        assert!(strip.move_column(&CycleDirection::Prev));
+        assert_eq!(strip.focused, 1);
+        assert_eq!(strip.column_of(3), Some(1));
+        assert_eq!(strip.column_of(2), Some(2));
+        assert!(strip.move_column(&CycleDirection::Next));
+        assert!(!strip.move_column(&CycleDirection::Next));
+        assert_eq!(strip.column_of(3), Some(2));
+    }
+
+    #[test]
+    fn consume_and_expel() {
+        let mut strip = ScrollingStrip::new(0.5);
+        strip.sync(&[1, 2]);
+        assert!(strip.consume(&CycleDirection::Prev));
+        assert_eq!(strip.columns.len(), 1);
+        assert_eq!(strip.columns[0].hwnds, vec![1, 2]);
+        assert_eq!(
+            strip.layout(AREA),
+            vec![
+                (1, LayoutRect::new(0, 30, 1000, 500)),
+                (2, LayoutRect::new(0, 530, 1000, 500)),
+            ]
+        );
+        assert!(strip.expel());
+        assert_eq!(strip.columns.len(), 2);
+        assert_eq!(strip.focused_hwnd(), Some(2));
+        assert!(!strip.expel());
+    }
+
+    #[test]
+    fn swap_keeps_columns() {
+        let mut strip = ScrollingStrip::new(0.5);
+        strip.sync(&[1, 2, 3]);
+        strip.columns[2].width = 1.0;
+        assert!(strip.swap(1, 3));
+        assert_eq!(strip.column_of(3), Some(0));
+        assert_eq!(strip.column_of(1), Some(2));
+        assert_eq!(strip.columns[2].width, 1.0);
+        assert!(!strip.swap(7, 8));
+    }
+
+    #[test]
+    fn cycle_width_uses_size_factor() {
+        let mut strip = ScrollingStrip::new(0.5);
+        strip.insert(1);
+        let size_factor = [1.0, 0.75, 0.5];
+        strip.cycle_width(&CycleDirection::Next, &size_factor);
+        assert_eq!(strip.columns[0].width, 1.0);
+        strip.cycle_width(&CycleDirection::Prev, &size_factor);
+        assert_eq!(strip.columns[0].width, 0.5);
+    }
+}
@@ -6,19 +6,24 @@ use crate::{
         app_window::AppWindow,
         color::{self},
         config::NtekConfig,
-        monitor_info::{self, get_monitors},
+        config_file::ConfigError,
+        monitor_info::get_monitors,
         overlay_handler::OverlayHandler,
+        session::{self, Session},
         statusbar::StatusbarWindow,
         win_api,
         win_event::WinEvent,
-        workspaces::Workspace,
     },
 };
 use flume::{Receiver, Sender};
 use parking_lot::Mutex;
 use std::{
+    path::PathBuf,
     str::FromStr,
-    sync::{Arc, OnceLock},
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Duration,
 };
 use windows::{
@@ -34,6 +39,13 @@ use windows::{
 pub const STATUSBAR_HEIGHT: f32 = 30.0;
 pub const WM_UPDATE_STATUSBAR: u32 = WM_USER + 1;
 pub const WM_UPDATE_BORDER: u32 = WM_USER + 2;
+const SESSION_INTERVAL: Duration = Duration::from_secs(2);
+// how long shutting down waits for a handler some other thread is holding
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+const WINEVENT_THREAD: &str = "winevents";
+const WINHOOK_THREAD: &str = "winhook";
+/// threads nothing works without, a panic on one of them ends the process
+pub const EVENT_LOOP_THREADS: &[&str] = &["main", WINEVENT_THREAD, WINHOOK_THREAD];
 
 static WINEVENT_CHANNEL: OnceLock<(
     Sender<(WinEvent, AppWindow)>,
@@ -69,6 +81,8 @@ pub struct OverlayManager {
     // statusbar: Shared<Vec<isize>>,
     // borders: Shared<Vec<isize>>,
     app_handler: Shared<OverlayHandler>,
+    session_path: Option<PathBuf>,
+    shut_down: AtomicBool,
 }
 
 impl OverlayManager {
@@ -82,30 +96,34 @@ impl OverlayManager {
         Self::spawn_statusbar_service(statusbar_hwnds.clone());
 
         let mut handler = OverlayHandler::new();
-        handler.monitors = monitor_info::get_monitors();
-        handler.blacklist = config.blacklist.clone();
-        handler.size_factor = config.size_factor.clone();
         handler.statusbar = statusbar_hwnds.clone();
         handler.border_overlay = border_overlay.clone();
-        handler.user_widgets.lock().workspaces = config
-            .workspaces
-            .iter()
-            .enumerate()
-            .map(|(i, ws)| Workspace {
-                text: ws.to_string(),
-                active: i == 0,
-                hwnds: Vec::new(),
-            })
-            .collect();
+        // monitors first, the workspace list depends on how many there are
+        let monitors = handler.backend.get_monitors();
+        handler.set_monitors(monitors);
+        handler.apply_config(&config);
+        let session_path = session::session_path();
+        if let Some(path) = &session_path {
+            match Session::load(path) {
+                Ok(session) => handler.restore_session(session),
+                Err(err) => eprintln!("Error loading session {}: {err}", path.display()),
+            }
+        }
         handler.spawn_widget();
+        let session_dirty = handler.session_dirty.clone();
         let app_handler = Arc::new(Mutex::new(handler));
 
         Self::spawn_winevent_listener_service(border_overlay.clone(), app_handler.clone());
+        if let Some(path) = &session_path {
+            Self::spawn_session_service(path.clone(), app_handler.clone(), session_dirty);
+        }
 
         Self {
             // statusbar: statusbar_hwnds,
             // borders: border_hwnds,
             app_handler,
+            session_path,
+            shut_down: AtomicBool::new(false),
         }
     }
 
@@ -117,6 +135,34 @@ impl OverlayManager {
         f(&mut handler)
     }
 
+    /// swap a freshly loaded config into the running handler
+    pub fn reload(&self, config: &NtekConfig) {
+        self.with_handler(|handler| handler.apply_config(config));
+    }
+    /// save the session and put every parked window back on screen, only the
+    /// first call does anything. a panic can leave the handler locked, this
+    /// gives up instead of hanging
+    pub fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let Some(handler) = self.app_handler.try_lock_for(SHUTDOWN_TIMEOUT) else {
+            eprintln!("Handler is busy, parked windows stay where they are");
+            return;
+        };
+        if let (Some(path), true) = (&self.session_path, handler.session_ready) {
+            if let Err(err) = handler.session().save(path) {
+                eprintln!("Error saving session {}: {err}", path.display());
+            }
+        }
+        handler.restore_parked_windows();
+    }
+    /// keeps whatever config is active and shows why the new one was rejected
+    pub fn show_config_error(&self, err: &ConfigError) {
+        eprintln!("Error reloading config: {err}");
+        self.with_handler(|handler| handler.show_config_error(err));
+    }
+
     //==============================================================================//
     // tag         : INTERNAL FUNCTION
     // description : -
@@ -126,86 +172,125 @@ impl OverlayManager {
         border_overlay: OptBorderOverlay,
         handler: Shared<OverlayHandler>,
     ) {
-        std::thread::spawn(move || {
-            while let Ok((ev, app_window)) = channel_receiver().recv() {
-                // if let Some(app) = app_window.get_app_info() {
-                //     eprintln!("{:?} {}{}", ev, app.exe, app.title);
-                // }
-                match ev {
-                    WinEvent::ObjectNamechange => {
-                        if let Some(app) = app_window.get_app_info() {
-                            let mut handler = handler.lock();
-                            handler.update_app_title(&app);
+        let spawned = std::thread::Builder::new()
+            .name(WINEVENT_THREAD.into())
+            .spawn(move || {
+                while let Ok((ev, app_window)) = channel_receiver().recv() {
+                    // if let Some(app) = app_window.get_app_info() {
+                    //     eprintln!("{:?} {}{}", ev, app.exe, app.title);
+                    // }
+                    match ev {
+                        WinEvent::ObjectNamechange => {
+                            if let Some(app) = app_window.get_app_info() {
+                                let mut handler = handler.lock();
+                                handler.update_app_title(&app);
+                            }
                         }
-                    }
-                    WinEvent::ObjectDestroy => {
-                        //delete app from app_list
-                        if let Some(app) = app_window.get_app_info() {
-                            handler.lock().delete_app(&app);
-                            //Self::update_border(&app, border_overlay.clone());
+                        WinEvent::ObjectDestroy => {
+                            //delete app from app_list
+                            if let Some(app) = app_window.get_app_info() {
+                                handler.lock().delete_app(&app);
+                                //Self::update_border(&app, border_overlay.clone());
+                            }
                         }
-                    }
-                    WinEvent::ObjectCreate => {
-                        //insert app into app_list
-                        // this execute once every start app
-                        if let Some(app) = app_window.get_app_info() {
-                            handler.lock().update_apps(app, ev);
+                        WinEvent::ObjectCreate => {
+                            //insert app into app_list
+                            // this execute once every start app
+                            if let Some(app) = app_window.get_app_info() {
+                                handler.lock().update_apps(app, ev);
+                            }
                         }
-                    }
-                    WinEvent::ObjectShow => {
-                        //this is new spawn app
-                        // add them to app_list
-                        if let Some(app) = app_window.get_app_info() {
-                            handler.lock().update_apps(app, ev);
+                        WinEvent::ObjectShow => {
+                            //this is new spawn app
+                            // add them to app_list
+                            if let Some(app) = app_window.get_app_info() {
+                                handler.lock().update_apps(app, ev);
+                            }
                         }
-                    }
-                    WinEvent::Done => {
-                        //this is called once per out start up
-                        // when the active app listing are done
-                        if let Ok(app) = Self::init_active_appinfo(&handler) {
-                            handler.lock().update_apps(app, ev);
+                        WinEvent::Done => {
+                            //this is called once per out start up
+                            // when the active app listing are done
+                            if let Ok(app) = Self::init_active_appinfo(&handler) {
+                                handler.lock().update_apps(app, ev);
+                            }
                         }
-                    }
-                    WinEvent::SystemCaptureend
-                    | WinEvent::SystemMovesizeend
-                    | WinEvent::SystemMinimizeend => {
-                        if let Some(app) = app_window.get_app_info() {
-                            let mut handler = handler.lock();
-                            handler.update_border(&app);
-                            handler.update_apps(app, ev);
+                        WinEvent::SystemCaptureend
+                        | WinEvent::SystemMovesizeend
+                        | WinEvent::SystemMinimizeend => {
+                            if let Some(app) = app_window.get_app_info() {
+                                let mut handler = handler.lock();
+                                handler.update_border(&app);
+                                handler.update_apps(app, ev);
+                            }
                         }
-                    }
-                    WinEvent::ObjectLocationchange => {
-                        if let Some(app) = app_window.get_app_info() {
-                            // {
-                            //     handler.lock().update_border(&app, border_overlay.clone());
-                            // }
-                            if let Ok(maximized) = win_api::is_window_maximized(hwnd!(app.hwnd)) {
-                                if maximized {
-                                    handler.lock().fake_maximize();
+                        WinEvent::ObjectLocationchange => {
+                            if let Some(app) = app_window.get_app_info() {
+                                // {
+                                //     handler.lock().update_border(&app, border_overlay.clone());
+                                // }
+                                if let Ok(maximized) = win_api::is_window_maximized(hwnd!(app.hwnd))
+                                {
+                                    if maximized {
+                                        handler.lock().fake_maximize();
+                                    }
+                                }
+                                {
+                                    let mut handler = handler.lock();
+                                    handler.current_active_app.map(|a| {
+                                        if app.hwnd == a {
+                                            handler.update_border(&app);
+                                        }
+                                    });
+                                    handler.update_apps(app, ev);
                                 }
                             }
-                            {
+                        }
+                        WinEvent::SystemForeground => {
+                            if let Some(app) = app_window.get_app_info() {
                                 let mut handler = handler.lock();
-                                handler.current_active_app.map(|a| {
-                                    if app.hwnd == a {
-                                        handler.update_border(&app);
-                                    }
-                                });
+                                handler.update_border(&app);
+                                handler.update_active_app(app.hwnd);
                                 handler.update_apps(app, ev);
+                                handler.reset_size_selector();
                             }
                         }
+                        _ => {}
                     }
-                    WinEvent::SystemForeground => {
-                        if let Some(app) = app_window.get_app_info() {
-                            let mut handler = handler.lock();
-                            handler.update_border(&app);
-                            handler.update_active_app(app.hwnd);
-                            handler.update_apps(app, ev);
-                            handler.reset_size_selector();
-                        }
-                    }
-                    _ => {}
+                }
+            });
+        if let Err(err) = spawned {
+            eprintln!("Error starting {WINEVENT_THREAD} {err}");
+        }
+    }
+    /// saves the session when the handler marked it dirty, the handler is only
+    /// locked then. a change of title alone isn't worth a write
+    fn spawn_session_service(
+        path: PathBuf,
+        handler: Shared<OverlayHandler>,
+        dirty: Arc<AtomicBool>,
+    ) {
+        std::thread::spawn(move || {
+            let mut last: Option<Session> = None;
+            loop {
+                std::thread::sleep(SESSION_INTERVAL);
+                if !dirty.swap(false, Ordering::Relaxed) {
+                    continue;
+                }
+                let session = {
+                    let handler = handler.lock();
+                    handler.session_ready.then(|| handler.session())
+                };
+                let Some(session) = session else {
+                    // the windows found at startup aren't placed yet, try again later
+                    dirty.store(true, Ordering::Relaxed);
+                    continue;
+                };
+                if last.as_ref().is_some_and(|last| last.same_layout(&session)) {
+                    continue;
+                }
+                match session.save(&path) {
+                    Ok(()) => last = Some(session),
+                    Err(err) => eprintln!("Error saving session {}: {err}", path.display()),
                 }
             }
         });
@@ -285,35 +370,40 @@ impl OverlayManager {
         });
     }
     fn init_winhook() {
-        std::thread::spawn(|| {
-            if let Err(err) = unsafe { EnumWindows(Some(Self::init_applist), LPARAM(0)) } {
-                eprintln!("Error Listing {err}")
-            }
-            channel_send(WinEvent::Done, AppWindow::default());
-            unsafe {
-                SetWinEventHook(
-                    EVENT_MIN,
-                    EVENT_MAX,
-                    None,
-                    Some(Self::win_event_hook),
-                    0,
-                    0,
-                    WINEVENT_OUTOFCONTEXT,
-                )
-            };
-
-            let mut msg: MSG = MSG::default();
-            loop {
+        let spawned = std::thread::Builder::new()
+            .name(WINHOOK_THREAD.into())
+            .spawn(|| {
+                if let Err(err) = unsafe { EnumWindows(Some(Self::init_applist), LPARAM(0)) } {
+                    eprintln!("Error Listing {err}")
+                }
+                channel_send(WinEvent::Done, AppWindow::default());
                 unsafe {
-                    if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
-                        break;
+                    SetWinEventHook(
+                        EVENT_MIN,
+                        EVENT_MAX,
+                        None,
+                        Some(Self::win_event_hook),
+                        0,
+                        0,
+                        WINEVENT_OUTOFCONTEXT,
+                    )
+                };
+
+                let mut msg: MSG = MSG::default();
+                loop {
+                    unsafe {
+                        if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                            break;
+                        }
+                        let _ = TranslateMessage(&msg);
+                        DispatchMessageW(&msg);
                     }
-                    let _ = TranslateMessage(&msg);
-                    DispatchMessageW(&msg);
+                    std::thread::sleep(Duration::ZERO);
                 }
-                std::thread::sleep(Duration::ZERO);
-            }
-        });
+            });
+        if let Err(err) = spawned {
+            eprintln!("Error starting {WINHOOK_THREAD} {err}");
+        }
     }
     extern "system" fn init_applist(hwnd: HWND, _lparam: LPARAM) -> BOOL {
         if unsafe { IsWindowVisible(hwnd) } == FALSE {
@@ -2,11 +2,22 @@ pub mod animation;
 pub mod app_border;
 pub mod app_info;
 pub mod app_window;
+pub mod backend;
 pub mod color;
 pub mod config;
+pub mod config_check;
+pub mod config_file;
+pub mod events;
+pub mod ipc;
+pub mod layout;
 pub mod manager;
 pub mod monitor_info;
 mod overlay_handler;
+pub mod rescue;
+pub mod rules;
+pub mod scratchpad;
+pub mod session;
+pub mod snapshot;
 pub mod statusbar;
 pub mod sys;
 pub mod widget;
@@ -9,7 +9,7 @@ use windows::{
     core::BOOL,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct StatusbarMonitorInfo {
     pub handle: isize,
     pub index: usize,
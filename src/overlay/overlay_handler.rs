@@ -1,30 +1,49 @@
-use crate::{
-    hwnd,
-    overlay::{
-        animation,
-        app_border::BorderInfo,
-        app_info::{AppInfo, AppPosition, AppSize, Column, SizeRatio},
-        color,
-        config::CycleDirection,
-        manager::{OptBorderOverlay, STATUSBAR_HEIGHT, Shared, WM_UPDATE_STATUSBAR},
-        monitor_info::StatusbarMonitorInfo,
-        statusbar::{SlotText, StatusBar, StatusBarFont, Visibility},
-        sys::{SystemInfo, format_speed},
-        widget::{SlotGrid, WidgetSlots, WorkspaceIndicatorPosition},
-        win_api,
-        win_event::WinEvent,
-        workspaces::{Hwnd, HwndItem, Workspace},
+use crate::overlay::{
+    animation,
+    app_border::BorderInfo,
+    app_info::{AppInfo, AppPosition, AppSize, Column, SizeRatio},
+    backend::{Win32Backend, WindowBackend},
+    color,
+    config::{CycleDirection, Direction, LayoutKind, MonitorTarget, NtekConfig},
+    config_file::ConfigError,
+    events::{self, Event, EventKind},
+    layout::{
+        self, GapSettings, Gaps, LayoutRect,
+        master_stack::{self, MasterStack},
+        monocle::Monocle,
+        neighbour,
+        scrolling::ScrollingStrip,
     },
+    manager::{OptBorderOverlay, STATUSBAR_HEIGHT, Shared, WM_UPDATE_STATUSBAR},
+    monitor_info::StatusbarMonitorInfo,
+    rescue,
+    rules::{GridCell, Rules},
+    scratchpad::{self, Scratchpads},
+    session::{SavedWindow, SavedWorkspace, Session},
+    snapshot::StateSnapshot,
+    statusbar::{SlotText, StatusBar, StatusBarFont, Visibility},
+    sys::{SystemInfo, format_speed},
+    widget::{SlotGrid, WidgetSlots, WorkspaceIndicatorPosition},
+    win_api,
+    win_event::WinEvent,
+    workspaces::{Hwnd, HwndItem, Workspace, WorkspaceInfo},
 };
 use anyhow::{Context, Result, anyhow, bail};
 use parking_lot::Mutex;
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
     thread,
     time::Duration,
 };
 
+const CONFIG_ERROR_SLOT: &str = "config-error";
+// size of a window floated for the first time, share of the usable area
+const FLOAT_RATIO: f32 = 0.6;
+
 #[derive(Debug)]
 struct AppProps<'a> {
     monitor: &'a StatusbarMonitorInfo,
@@ -47,14 +66,46 @@ pub struct OverlayHandler {
     pub apps: HashMap<isize, AppInfo>,
     pub monitors: Vec<StatusbarMonitorInfo>,
     pub size_factor: Vec<f32>,
+    pub column_width: f32,
+    pub gaps: GapSettings,
     pub user_widgets: Shared<WidgetSlots>,
     pub grid_app_position: usize,
     pub border_overlay: OptBorderOverlay,
     pub top_most_apps: HashSet<isize>,
+    // left out of automatic arrangement, kept above the tiles
+    pub floating_apps: HashSet<isize>,
+    // floating windows that are only topmost because they float
+    pub float_top_most: HashSet<isize>,
+    // last visible rect of every window that floated, even after it was tiled again
+    pub floating_rects: HashMap<isize, LayoutRect>,
+    pub rules: Rules,
+    // border colour a rule gives the window, resolved when it is placed and on reload
+    pub rule_borders: HashMap<isize, u32>,
+    // monitor of the last focused window, focus leaving it is a monitor event
+    focused_monitor: Option<usize>,
+    // saved windows no live window has claimed yet
+    restore: Session,
+    // set once the windows found at startup are placed, saving before that
+    // would overwrite the session with everything on workspace 0
+    pub session_ready: bool,
+    // raised when workspace membership or layout changed, the session
+    // service saves and lowers it
+    pub session_dirty: Arc<AtomicBool>,
+    // what `create_workspace` starts from, layout and master stack of the config
+    pub workspace_template: Workspace,
+    // workspace names of the last config, a reload only rebuilds the list when they change
+    pub config_workspaces: Vec<(Option<usize>, String)>,
+    // windows on no workspace, shown and hidden by name
+    pub scratchpads: Scratchpads,
+    pub backend: Arc<dyn WindowBackend>,
 }
 impl OverlayHandler {
     pub fn new() -> Self {
+        Self::with_backend(Arc::new(Win32Backend))
+    }
+    pub fn with_backend(backend: Arc<dyn WindowBackend>) -> Self {
         Self {
+            backend,
             height_selector_index: 0,
             width_selector_index: 0,
             current_active_app: None,
@@ -63,12 +114,25 @@ impl OverlayHandler {
             blacklist: vec![],
             monitors: vec![],
             size_factor: vec![],
+            column_width: 0.5,
+            gaps: GapSettings::default(),
             top_most_apps: HashSet::new(),
+            floating_apps: HashSet::new(),
+            float_top_most: HashSet::new(),
+            floating_rects: HashMap::new(),
+            rules: Rules::default(),
+            rule_borders: HashMap::new(),
+            focused_monitor: None,
+            restore: Session::default(),
+            session_ready: false,
+            session_dirty: Arc::new(AtomicBool::new(false)),
+            workspace_template: Workspace::new("", vec![]),
+            config_workspaces: vec![],
+            scratchpads: Scratchpads::default(),
             grid_app_position: 0,
             border_overlay: Arc::new(Mutex::new(None)),
             user_widgets: Arc::new(Mutex::new(WidgetSlots {
                 workspace_indicator: WorkspaceIndicatorPosition::Left,
-                hwnd: None,
                 ..Default::default()
             })),
         }
@@ -80,6 +144,7 @@ impl OverlayHandler {
         app_name: &str,
         monitor: usize,
     ) {
+        let mut previous = None;
         {
             let mut guard = self.user_widgets.lock();
             let workspaces = guard.get_workspaces();
@@ -89,45 +154,205 @@ impl OverlayHandler {
                     vec![HwndItem::new(hwnd, app_name, monitor)],
                 ));
             } else {
-                for ws in &mut *workspaces {
+                for (index, ws) in workspaces.iter_mut().enumerate() {
+                    if let Some(item) = ws.hwnds.iter().find(|h| h.hwnd == hwnd) {
+                        previous = Some((index, item.monitor));
+                    }
                     ws.hwnds.retain(|h| h.hwnd != hwnd);
+                    for tree in ws.bsp.values_mut() {
+                        tree.remove(hwnd);
+                    }
                 }
                 if let Some(ws) = workspaces.get_mut(workspace_index) {
+                    let existing = ws.hwnds_on_monitor(monitor);
                     ws.hwnds.push(HwndItem::new(hwnd, app_name, monitor));
+                    let tree = ws.bsp.entry(monitor).or_default();
+                    tree.sync(&existing);
+                    tree.insert(hwnd, self.current_active_app);
                 }
             }
         }
+        self.mark_session_dirty();
         if self.apps.contains_key(&hwnd) {
             self.reorder_app_pos_in_workspace();
         }
+        if let Some((index, moni)) = previous {
+            if (index, moni) != (workspace_index, monitor) {
+                self.apply_layout(index, moni);
+            }
+        }
+        self.apply_layout(workspace_index, monitor);
     }
     pub fn update_active_app(&mut self, hwnd: isize) {
         self.current_active_app = Some(hwnd);
     }
     pub fn reset_size_selector(&mut self) {}
     pub fn delete_app(&mut self, app: &AppInfo) {
-        self.apps.remove(&app.hwnd);
+        let removed = self.apps.remove(&app.hwnd);
+        self.floating_apps.remove(&app.hwnd);
+        self.float_top_most.remove(&app.hwnd);
+        self.floating_rects.remove(&app.hwnd);
+        self.rule_borders.remove(&app.hwnd);
         if let Some(ref overlay) = *self.border_overlay.lock() {
             overlay.clear_focus();
             overlay.remove_topmost(app.hwnd as isize);
         }
-        if let Some(ws) = self
-            .user_widgets
-            .lock()
-            .workspaces
-            .iter_mut()
-            .find(|ws| ws.hwnds.iter().any(|h| h.hwnd == app.hwnd))
-        {
-            ws.hwnds.retain(|h| h.hwnd != app.hwnd);
+        let relayout = self.unassign_app(app.hwnd);
+        if relayout.is_some() || self.scratchpads.name_of(app.hwnd).is_some() {
             self.current_active_app = None;
         }
+        self.scratchpads.forget(app.hwnd);
+        if removed.is_some() {
+            events::publish(EventKind::Destroy, || Event::Destroy {
+                app: app.clone(),
+                workspace: relayout
+                    .and_then(|(index, _)| self.get_all_workspaces().into_iter().nth(index)),
+            });
+        }
+    }
+    /// takes `hwnd` off its workspace and lays that out again, returns which
+    /// workspace and monitor it was on
+    fn unassign_app(&mut self, hwnd: Hwnd) -> Option<(usize, usize)> {
+        let mut relayout = None;
+        {
+            let mut guard = self.user_widgets.lock();
+            for (index, ws) in guard.workspaces.iter_mut().enumerate() {
+                if let Some(item) = ws.hwnds.iter().find(|h| h.hwnd == hwnd) {
+                    relayout = Some((index, item.monitor));
+                    ws.hwnds.retain(|h| h.hwnd != hwnd);
+                    ws.focus_history.retain(|h| *h != hwnd);
+                }
+                for tree in ws.bsp.values_mut() {
+                    tree.remove(hwnd);
+                }
+            }
+        }
+        let (index, monitor) = relayout?;
+        self.mark_session_dirty();
+        self.apply_layout(index, monitor);
+        relayout
     }
     fn filter_app(&mut self, app: &AppInfo) -> bool {
         let is_blacklist = self.blacklist.contains(&app.exe);
         let total_width: i32 = self.monitors.iter().map(|m| m.width).sum();
         let is_fullscreen_explorer =
             app.exe.contains("explorer.exe") && app.size.width == total_width;
-        is_blacklist || is_fullscreen_explorer
+        is_blacklist || is_fullscreen_explorer || self.rules.is_ignored(app)
+    }
+    /// puts a window that just showed up on its workspace. `workspace` is where
+    /// it goes without a rule, `None` for whatever is showing on its monitor.
+    /// rules can pick another workspace or monitor, a grid cell, float it or
+    /// keep it on top, `app` is updated to where the window ends up
+    fn place_new_app(&mut self, app: &mut AppInfo, workspace: Option<usize>) {
+        if self.scratchpads.name_of(app.hwnd).is_some() {
+            return;
+        }
+        let rule = self.rules.resolve(app);
+        match rule.border {
+            Some(color) => self.rule_borders.insert(app.hwnd, color),
+            None => self.rule_borders.remove(&app.hwnd),
+        };
+        // only the first window of a scratchpad, the rest are normal windows
+        if let Some(name) = rule.scratchpad.as_deref() {
+            if self.adopt_scratchpad(app, name).is_some() {
+                return;
+            }
+        }
+        // where it was last session beats the rules, the user put it there
+        let saved = if self.apps.contains_key(&app.hwnd) {
+            None
+        } else {
+            self.restore.claim(app)
+        };
+        let current = self.monitor_index_for(app.hwnd);
+        let mut monitor = saved
+            .as_ref()
+            .map(|s| s.window.monitor)
+            .or(rule.monitor)
+            .filter(|m| *m < self.monitors.len())
+            .unwrap_or(current);
+        let (workspace, active) = {
+            let guard = self.user_widgets.lock();
+            let name = saved
+                .as_ref()
+                .map(|s| &s.workspace)
+                .or(rule.workspace.as_ref());
+            // the monitor's own workspace of that name first
+            let named = name.and_then(|name| {
+                let find = |mine: bool| {
+                    guard
+                        .workspaces
+                        .iter()
+                        .position(|ws| &ws.text == name && (!mine || ws.belongs_to(monitor)))
+                };
+                find(true).or_else(|| find(false))
+            });
+            // a workspace another monitor has takes the window over there
+            if let Some(owner) = named
+                .and_then(|i| guard.workspaces[i].monitor)
+                .filter(|owner| *owner < self.monitors.len())
+            {
+                monitor = owner;
+            }
+            let active = guard.get_active_workspace_for_monitor(monitor);
+            let workspace = named
+                .or(workspace)
+                .filter(|i| {
+                    guard
+                        .workspaces
+                        .get(*i)
+                        .is_some_and(|ws| ws.belongs_to(monitor))
+                })
+                .unwrap_or(active);
+            (workspace, active)
+        };
+        let float = saved.as_ref().map_or(rule.float, |s| s.window.floating);
+        if float {
+            self.floating_apps.insert(app.hwnd);
+        }
+        if float && !rule.top_most && !self.top_most_apps.contains(&app.hwnd) {
+            self.float_top_most.insert(app.hwnd);
+        }
+        if rule.top_most || float {
+            self.set_top_most(app.hwnd, true);
+        }
+        let rect = match (saved.and_then(|s| s.window.rect), rule.grid) {
+            (Some(rect), _) => Some(rect),
+            (None, Some(cell)) => self.grid_cell_rect(monitor, cell),
+            (None, None) if monitor != current => self.rect_on_monitor(app, current, monitor),
+            (None, None) => None,
+        };
+        if let Some(rect) = rect {
+            let (position, size) =
+                self.animate_to_rect(app, rect, animation::AnimationEasing::EaseOutQuart);
+            app.position = position;
+            app.size = size;
+        }
+        if float {
+            self.floating_rects.insert(app.hwnd, self.visible_rect(app));
+        }
+        self.assign_app_to_workspace(workspace, app.hwnd, &app.exe, monitor);
+        if workspace != active {
+            self.park_app(app, monitor);
+        }
+    }
+    /// a window landing on a workspace that isn't showing goes off screen like
+    /// the rest of it, `go_to_workspace` brings it back
+    fn park_app(&self, app: &mut AppInfo, monitor: usize) {
+        let statusbar_height = self.get_statusbar_height(monitor);
+        if let Some(item) = self
+            .user_widgets
+            .lock()
+            .workspaces
+            .iter_mut()
+            .flat_map(|ws| ws.hwnds.iter_mut())
+            .find(|h| h.hwnd == app.hwnd)
+        {
+            item.parked_position = Some(app.position.y.max(statusbar_height));
+        }
+        self.backend
+            .set_position(app.hwnd, app.position.x, layout::PARKED_Y);
+        app.position.y = layout::PARKED_Y;
     }
 
     pub fn update_app_title(&mut self, app: &AppInfo) {
@@ -148,35 +373,63 @@ impl OverlayHandler {
                 );
             }
         }
+        if let Some(known) = self.apps.get_mut(&app.hwnd) {
+            if known.title != app.title {
+                known.title = app.title.clone();
+                events::publish(EventKind::Title, || Event::Title {
+                    app: app.clone(),
+                    workspace: self.workspace_info_for(app.hwnd),
+                });
+            }
+        }
+    }
+    /// focus events, plus a monitor event when focus landed on another monitor
+    fn publish_focus(&mut self, hwnd: Hwnd) {
+        if !self.apps.contains_key(&hwnd) {
+            return;
+        }
+        let monitor = self.monitor_index_for(hwnd);
+        if self.focused_monitor.replace(monitor) != Some(monitor) {
+            events::publish(EventKind::Monitor, || Event::Monitor {
+                monitor,
+                workspace: self.workspace_info_for(hwnd),
+            });
+        }
+        events::publish(EventKind::Focus, || Event::Focus {
+            app: self.apps[&hwnd].clone(),
+            workspace: self.workspace_info_for(hwnd),
+        });
     }
 
-    pub fn update_apps(&mut self, app: AppInfo, event: WinEvent) {
+    pub fn update_apps(&mut self, mut app: AppInfo, event: WinEvent) {
+        let hwnd = app.hwnd;
         match event {
             WinEvent::ObjectCreate => {
                 if self.filter_app(&app) {
                     return;
                 }
-                if win_api::is_top_most(hwnd!(app.hwnd)) {
+                if self.backend.is_top_most(app.hwnd) {
                     self.top_most_apps.insert(app.hwnd);
                 }
-                let monitor =
-                    win_api::get_monitor_index(hwnd!(app.hwnd), &self.monitors).unwrap_or(0);
-                self.assign_app_to_workspace(0, app.hwnd, &app.exe, monitor);
+                self.place_new_app(&mut app, Some(0));
             }
             WinEvent::ObjectShow => {
                 if self.filter_app(&app) {
                     return;
                 }
-                let monitor =
-                    win_api::get_monitor_index(hwnd!(app.hwnd), &self.monitors).unwrap_or(0);
-                let active_workspace = self
-                    .user_widgets
-                    .lock()
-                    .get_active_workspace_for_monitor(monitor);
-                self.assign_app_to_workspace(active_workspace, app.hwnd, &app.exe, monitor);
+                self.place_new_app(&mut app, None);
             }
             WinEvent::Done => {
                 self.update_app_parking_position(app.hwnd, app.position.y);
+                self.apply_active_layouts();
+                self.session_ready = true;
+            }
+            WinEvent::SystemMovesizeend => {
+                self.mark_session_dirty();
+                if self.floating_apps.contains(&app.hwnd) {
+                    self.floating_rects
+                        .insert(app.hwnd, self.visible_rect(&app));
+                }
             }
             WinEvent::ObjectLocationchange => {
                 self.update_border(&app);
@@ -202,6 +455,7 @@ impl OverlayHandler {
                 {
                     self.update_app_title(&app);
                 }
+                self.follow_focus(app.hwnd);
             }
             _ => {}
         }
@@ -217,17 +471,26 @@ impl OverlayHandler {
                 ..app
             }
         } else {
-            self.apps.insert(app.hwnd, app);
+            self.apps.insert(hwnd, app);
+            events::publish(EventKind::Create, || Event::Create {
+                app: self.apps[&hwnd].clone(),
+                workspace: self.workspace_info_for(hwnd),
+            });
+        }
+        if matches!(event, WinEvent::SystemForeground) {
+            self.remember_focus(hwnd);
+            self.publish_focus(hwnd);
         }
     }
 
     pub fn update_app_parking_position(&mut self, hwnd: Hwnd, ypos: i32) {
-        for ws in self
-            .user_widgets
-            .lock()
+        let mut guard = self.user_widgets.lock();
+        let showing = guard.active_workspace_per_monitor.clone();
+        for (_, ws) in guard
             .workspaces
             .iter_mut()
-            .filter(|w| w.active)
+            .enumerate()
+            .filter(|(i, _)| showing.contains(i))
         {
             if let Some(h) = ws.hwnds.iter_mut().find(|h| h.hwnd == hwnd) {
                 h.parked_position = Some(ypos);
@@ -235,7 +498,9 @@ impl OverlayHandler {
         }
     }
     fn monitor_index_for(&self, hwnd: Hwnd) -> usize {
-        win_api::get_monitor_index(hwnd!(hwnd), &self.monitors).unwrap_or(0)
+        self.backend
+            .get_monitor_index(hwnd, &self.monitors)
+            .unwrap_or(0)
     }
     pub fn get_statusbar_height(&self, monitor: usize) -> i32 {
         if monitor == 0 {
@@ -249,7 +514,7 @@ impl OverlayHandler {
         let monitor_index = self.monitor_index_for(active_hwnd);
         let monitor = self.monitors.get(monitor_index)?;
         let app = self.apps.get(&active_hwnd)?;
-        let (px, py) = win_api::get_rect_padding(active_hwnd);
+        let (px, py) = self.backend.get_rect_padding(active_hwnd);
 
         Some(AppProps {
             monitor: monitor,
@@ -263,41 +528,127 @@ impl OverlayHandler {
             column: &app.column,
         })
     }
+    /// gaps of whatever workspace is showing on `monitor`
+    fn gaps_for(&self, monitor: usize) -> Gaps {
+        let guard = self.user_widgets.lock();
+        let index = guard.get_active_workspace_for_monitor(monitor);
+        let name = guard
+            .workspaces
+            .get(index)
+            .map(|ws| ws.text.as_str())
+            .unwrap_or_default();
+        self.gaps.resolve(monitor, name)
+    }
+    /// usable area of `monitor` with the outer gap already taken off
+    fn gapped_area(&self, monitor: usize, gaps: Gaps) -> Option<LayoutRect> {
+        let area = layout::usable_area(
+            self.monitors.get(monitor)?,
+            self.get_statusbar_height(monitor),
+        );
+        Some(gaps.shrink(area))
+    }
     pub fn fake_maximize(&mut self) -> Option<()> {
         let props = self.get_props()?;
-        let width =
-            (self.size_factor[self.width_selector_index] * props.monitor.width as f32) as i32;
-        let height =
-            (self.size_factor[self.height_selector_index] * props.monitor.height as f32) as i32;
-        let toolbar_height = self.get_statusbar_height(self.monitor_index_for(props.active_hwnd));
+        let monitor = self.monitor_index_for(props.active_hwnd);
+        let area = self.gapped_area(monitor, self.gaps_for(monitor))?;
+        let width = (self.size_factor[self.width_selector_index] * area.width as f32) as i32;
+        let height = (self.size_factor[self.height_selector_index] * area.height as f32) as i32;
 
         let w = width + props.px;
-        let h = height + (props.py / 2) - toolbar_height;
-        let x = props.monitor.x + (-(props.px / 2));
-        let y = toolbar_height;
-        win_api::set_app_size_position(hwnd!(props.active_hwnd), x, y, w, h, true);
+        let h = height + (props.py / 2);
+        let x = area.x + (-(props.px / 2));
+        let y = area.y;
+        self.backend
+            .set_size_position(props.active_hwnd, x, y, w, h);
         Some(())
     }
+    pub fn update_gaps(&mut self, f: impl FnOnce(&mut GapSettings)) {
+        f(&mut self.gaps);
+        self.apply_active_layouts();
+    }
+
+    //==============================================================================//
+    // tag         : CONFIG
+    // description : live config reload
+    //==============================================================================//
+    pub fn apply_config(&mut self, config: &NtekConfig) {
+        self.blacklist = config.blacklist.clone();
+        self.rules = Rules::from_config(config);
+        self.rule_borders = self
+            .apps
+            .values()
+            .filter_map(|app| Some((app.hwnd, self.rules.resolve(app).border?)))
+            .collect();
+        self.size_factor = config.size_factor.clone();
+        self.width_selector_index = 0;
+        self.height_selector_index = 0;
+        self.column_width = config.column_width.unwrap_or(0.5);
+        self.gaps = config.gap_settings();
+        self.workspace_template = config.new_workspace("");
+        self.scratchpads.apply_config(config);
+        {
+            let mut guard = self.user_widgets.lock();
+            // workspaces made, renamed or moved since survive a reload that
+            // leaves the list alone
+            let workspaces = config.workspace_list(self.monitors.len());
+            if self.config_workspaces != workspaces {
+                guard.sync_workspaces(&workspaces, |name| config.new_workspace(name));
+                self.config_workspaces = workspaces;
+            }
+            guard.remove_slot(SlotGrid::Left, CONFIG_ERROR_SLOT);
+            guard.refresh_statusbar();
+        }
+        self.mark_session_dirty();
+        self.reorder_app_pos_in_workspace();
+        self.apply_active_layouts();
+        events::publish(EventKind::ConfigReload, || Event::ConfigReload {
+            workspaces: self.get_all_workspaces(),
+        });
+    }
+    pub fn show_config_error(&self, err: &ConfigError) {
+        self.user_widgets.lock().set_slot(
+            SlotGrid::Left,
+            CONFIG_ERROR_SLOT,
+            vec![
+                SlotText::new(" config ")
+                    .fg(color::DARK_FG)
+                    .bg(color::DANGER),
+                SlotText::new(err.to_string()).fg(color::DANGER),
+            ],
+        );
+    }
 
     //==============================================================================//
     // tag         : MONITOR BITS
     // description :
     //==============================================================================//
-    fn get_active_monitor(&self) -> usize {
-        win_api::get_monitor_index_from_cursor(&self.monitors)
+    pub fn get_active_monitor(&self) -> usize {
+        self.backend.get_monitor_index_from_cursor(&self.monitors)
+    }
+    /// every monitor gets a workspace to show
+    pub fn set_monitors(&mut self, monitors: Vec<StatusbarMonitorInfo>) {
+        self.user_widgets.lock().set_monitor_count(monitors.len());
+        self.monitors = monitors;
     }
     //==============================================================================//
     // tag         : APP POSITION and SIZE
     // description : this part where we manipulate size and position of the app
     //==============================================================================//
     // GETTER
-    pub fn get_all_apps(&self) {
-        todo!()
+    /// every managed window, ordered by hwnd
+    pub fn get_all_apps(&self) -> Vec<AppInfo> {
+        let mut apps: Vec<AppInfo> = self.apps.values().cloned().collect();
+        apps.sort_by_key(|app| app.hwnd);
+        apps
+    }
+    pub fn get_focused_app(&self) -> Option<AppInfo> {
+        self.apps.get(&self.current_active_app?).cloned()
     }
     // SETTER
     pub fn update_border(&self, app: &AppInfo) -> Option<()> {
         let active = self.current_active_app?;
         let is_top_most = self.top_most_apps.contains(&app.hwnd);
+        let is_floating = self.floating_apps.contains(&app.hwnd);
         // let is_top_most = win_api::is_top_most(hwnd!(active));
 
         let overlay = self.border_overlay.lock();
@@ -306,8 +657,8 @@ impl OverlayHandler {
         win_api::force_border_to_front(overlay.hwnd());
 
         const PADDING: i32 = 2;
-        let is_maximized = win_api::is_maximized(app.hwnd);
-        let (px, py) = win_api::get_rect_padding(app.hwnd);
+        let is_maximized = self.backend.is_maximized(app.hwnd);
+        let (px, py) = self.backend.get_rect_padding(app.hwnd);
         let y = if is_maximized {
             app.position.y + (py / 2)
         } else {
@@ -319,11 +670,17 @@ impl OverlayHandler {
             y: y + PADDING / 2,
             width: app.size.width - (px) - PADDING,
             height: app.size.height - (py) - PADDING,
-            color: if is_top_most {
-                color::Theme::WARNING
-            } else {
-                color::Theme::DANGER
-            },
+            color: self
+                .rule_borders
+                .get(&app.hwnd)
+                .copied()
+                .unwrap_or(if is_floating {
+                    color::Theme::PRIMARY
+                } else if is_top_most {
+                    color::Theme::WARNING
+                } else {
+                    color::Theme::DANGER
+                }),
             thickness: 2.0,
             radius: 5.0,
         };
@@ -346,18 +703,142 @@ impl OverlayHandler {
 
     pub fn toggle_top_most(&mut self) -> Option<()> {
         let active_app = self.current_active_app?;
-        let app = self.apps.get(&active_app)?;
-        let overlay = self.border_overlay.lock();
-        let overlay = overlay.as_ref()?;
-        let topmost = win_api::toggle_top_most(hwnd!(app.hwnd), overlay.hwnd());
-        if topmost {
-            self.top_most_apps.insert(app.hwnd);
+        let hwnd = self.apps.get(&active_app)?.hwnd;
+        // asked for by hand, un-floating leaves it alone from now on
+        self.float_top_most.remove(&hwnd);
+        self.set_top_most(hwnd, !self.top_most_apps.contains(&hwnd));
+        Some(())
+    }
+    /// floating windows leave the layout and go back to the rect they last had
+    /// while floating (centred the first time), on top of the tiles. a window
+    /// that was topmost before it floated stays topmost when it is tiled again
+    pub fn toggle_float(&mut self) -> Option<()> {
+        let hwnd = self.current_active_app?;
+        let app = self.apps.get(&hwnd)?.clone();
+        let monitor = self.monitor_index_for(hwnd);
+        if self.floating_apps.remove(&hwnd) {
+            self.floating_rects.insert(hwnd, self.visible_rect(&app));
+            if self.float_top_most.remove(&hwnd) {
+                self.set_top_most(hwnd, false);
+            }
         } else {
-            self.top_most_apps.remove(&app.hwnd);
+            let area = self.gapped_area(monitor, self.gaps_for(monitor))?;
+            let rect = self
+                .floating_rects
+                .get(&hwnd)
+                .copied()
+                .filter(|rect| rect.intersects(&area))
+                .unwrap_or_else(|| area.centered(FLOAT_RATIO));
+            self.floating_apps.insert(hwnd);
+            self.floating_rects.insert(hwnd, rect);
+            if !self.top_most_apps.contains(&hwnd) {
+                self.float_top_most.insert(hwnd);
+                self.set_top_most(hwnd, true);
+            }
+            let (position, size) =
+                self.animate_to_rect(&app, rect, animation::AnimationEasing::EaseOutQuart);
+            let app = self.apps.get_mut(&hwnd)?;
+            app.position = position;
+            app.size = size;
+        }
+        self.mark_session_dirty();
+        let workspace = self
+            .user_widgets
+            .lock()
+            .get_active_workspace_for_monitor(monitor);
+        self.apply_layout(workspace, monitor);
+        if let Some(app) = self.apps.get(&hwnd) {
+            self.update_border(app);
         }
         Some(())
     }
+    /// the border overlay is kept above whatever is topmost
+    fn set_top_most(&mut self, hwnd: Hwnd, top_most: bool) {
+        if self.top_most_apps.contains(&hwnd) == top_most {
+            return;
+        }
+        let border = self
+            .border_overlay
+            .lock()
+            .as_ref()
+            .map(|overlay| overlay.hwnd().0 as isize)
+            .unwrap_or_default();
+        if self.backend.toggle_top_most(hwnd, border) {
+            self.top_most_apps.insert(hwnd);
+        } else {
+            self.top_most_apps.remove(&hwnd);
+        }
+    }
 
+    pub fn focus_app(&self, hwnd: Hwnd) {
+        let border = self
+            .border_overlay
+            .lock()
+            .as_ref()
+            .map(|overlay| overlay.hwnd().0 as isize)
+            .unwrap_or_default();
+        self.backend.bring_to_front(hwnd, border);
+    }
+    fn move_app_to_rect(
+        &self,
+        hwnd: Hwnd,
+        rect: LayoutRect,
+        easing: animation::AnimationEasing,
+    ) -> Option<()> {
+        let app = self.apps.get(&hwnd)?;
+        self.animate_to_rect(app, rect, easing);
+        Some(())
+    }
+    /// `rect` is what should be visible, returns the outer position and size
+    fn animate_to_rect(
+        &self,
+        app: &AppInfo,
+        rect: LayoutRect,
+        easing: animation::AnimationEasing,
+    ) -> (AppPosition, AppSize) {
+        let (px, py) = self.backend.get_rect_padding(app.hwnd);
+        let position = AppPosition::new(rect.x - (px / 2), rect.y - (py / 2));
+        let size = AppSize::new(rect.width + px, rect.height + py);
+        self.backend.animate_window(
+            app.hwnd,
+            app.position,
+            position,
+            app.size.clone(),
+            size.clone(),
+            easing,
+        );
+        (position, size)
+    }
+    /// `cell` of the grid on `monitor`, inside the gaps
+    fn grid_cell_rect(&self, monitor: usize, (x, y, w, h): GridCell) -> Option<LayoutRect> {
+        let gaps = self.gaps_for(monitor);
+        let area = self.gapped_area(monitor, gaps)?;
+        let cell = LayoutRect::new(
+            area.x + (area.width as f32 * x) as i32,
+            area.y + (area.height as f32 * y) as i32,
+            (area.width as f32 * w) as i32,
+            (area.height as f32 * h) as i32,
+        );
+        Some(gaps.inset(cell, area))
+    }
+    /// what is on screen of `app`, without the invisible resize borders
+    fn visible_rect(&self, app: &AppInfo) -> LayoutRect {
+        let (px, py) = self.backend.get_rect_padding(app.hwnd);
+        LayoutRect::new(
+            app.position.x + (px / 2),
+            app.position.y + (py / 2),
+            app.size.width - px,
+            app.size.height - py,
+        )
+    }
+    /// visible rect of `app` at the same relative place and size on another monitor
+    fn rect_on_monitor(&self, app: &AppInfo, from: usize, to: usize) -> Option<LayoutRect> {
+        Some(layout::scale_rect(
+            self.visible_rect(app),
+            layout::usable_area(self.monitors.get(from)?, self.get_statusbar_height(from)),
+            layout::usable_area(self.monitors.get(to)?, self.get_statusbar_height(to)),
+        ))
+    }
     pub fn set_app_position(&self, x: i32, y: i32) {
         todo!()
     }
@@ -368,24 +849,16 @@ impl OverlayHandler {
         if grid.is_empty() {
             return None;
         }
-        self.grid_app_position = (self.grid_app_position + 1) % grid.len();
-        let props = self.get_props()?;
-        let moni = props.monitor;
-        let toolbar_height = self.get_statusbar_height(self.monitor_index_for(props.active_hwnd));
-        if let Some((x, y, w, h)) = grid.get(self.grid_app_position) {
-            let x = moni.x + ((moni.width as f32 * x) as i32 - (props.px / 2));
-            let y = (moni.height as f32 * y) as i32 - (props.py / 2) + toolbar_height;
-            let w = (moni.width as f32 * w) as i32 + props.px;
-            let h = (moni.height as f32 * h) as i32 + props.py - toolbar_height;
-            animation::animate_window(
-                props.active_hwnd,
-                props.position.clone(),
-                AppPosition::new(x, y),
-                props.size.clone(),
-                AppSize::new(w, h),
-                animation::AnimationEasing::EaseOutQuart,
-            );
-        }
+        self.move_to_grid_cell((self.grid_app_position + 1) % grid.len(), grid)
+    }
+    /// put the active app on `grid[index]`, cycling carries on from there
+    pub fn move_to_grid_cell(&mut self, index: usize, grid: &[GridCell]) -> Option<()> {
+        let cell = *grid.get(index)?;
+        self.grid_app_position = index;
+        let active_hwnd = self.current_active_app?;
+        let monitor = self.monitor_index_for(active_hwnd);
+        let rect = self.grid_cell_rect(monitor, cell)?;
+        self.move_app_to_rect(active_hwnd, rect, animation::AnimationEasing::EaseOutQuart);
         Some(())
     }
     pub fn cycle_app_width(&self, direction: &str) {
@@ -400,15 +873,146 @@ impl OverlayHandler {
     pub fn reset_all_position(&self) {
         todo!()
     }
-    pub fn close_active_app(&self) {
-        todo!()
+    pub fn close_active_app(&self) -> Option<()> {
+        let active = self.current_active_app?;
+        if let Err(err) = self.backend.close(active) {
+            eprintln!("Error closing app {err}");
+        }
+        Some(())
     }
     //==============================================================================//
     // tag         : WORKSPACE
     // description : this is workspace area
     //==============================================================================//
-    pub fn get_all_workspaces(&self) {
-        todo!()
+    pub fn get_all_workspaces(&self) -> Vec<WorkspaceInfo> {
+        let guard = self.user_widgets.lock();
+        guard
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(index, ws)| WorkspaceInfo {
+                index,
+                name: ws.text.clone(),
+                layout: ws.layout,
+                monitor: ws.monitor,
+                active_on: (0..self.monitors.len())
+                    .filter(|m| guard.get_active_workspace_for_monitor(*m) == index)
+                    .collect(),
+                windows: ws.hwnds.clone(),
+            })
+            .collect()
+    }
+    pub fn snapshot(&self) -> StateSnapshot {
+        let sorted = |set: &HashSet<Hwnd>| {
+            let mut hwnds: Vec<Hwnd> = set.iter().copied().collect();
+            hwnds.sort();
+            hwnds
+        };
+        let active_workspace_per_monitor = {
+            let guard = self.user_widgets.lock();
+            (0..self.monitors.len())
+                .map(|m| guard.get_active_workspace_for_monitor(m))
+                .collect()
+        };
+        StateSnapshot {
+            monitors: self.monitors.clone(),
+            active_workspace_per_monitor,
+            workspaces: self.get_all_workspaces(),
+            apps: self.get_all_apps(),
+            focused: self.current_active_app,
+            top_most: sorted(&self.top_most_apps),
+            floating: sorted(&self.floating_apps),
+        }
+    }
+    //==============================================================================//
+    // tag         : SESSION
+    // description : workspaces and their windows across restarts
+    //==============================================================================//
+    /// saved layouts go on right away, saved windows wait for a window to claim them
+    pub fn restore_session(&mut self, session: Session) {
+        session.apply_layouts(&mut self.user_widgets.lock().workspaces);
+        self.restore = session;
+    }
+    /// the session service saves on its next tick
+    pub fn mark_session_dirty(&self) {
+        self.session_dirty.store(true, Ordering::Relaxed);
+    }
+    pub fn session(&self) -> Session {
+        let guard = self.user_widgets.lock();
+        let workspaces = guard
+            .workspaces
+            .iter()
+            .map(|ws| SavedWorkspace {
+                name: ws.text.clone(),
+                layout: ws.layout,
+                master_count: ws.master_stack.master_count,
+                master_ratio: ws.master_stack.master_ratio,
+                windows: ws
+                    .hwnds
+                    .iter()
+                    .filter_map(|item| {
+                        let app = self.apps.get(&item.hwnd)?;
+                        let floating = self.floating_apps.contains(&item.hwnd);
+                        let rect = match self.floating_rects.get(&item.hwnd) {
+                            Some(rect) if floating => Some(*rect),
+                            _ if floating || ws.layout == LayoutKind::Floating => {
+                                Some(self.shown_rect(app, item))
+                            }
+                            _ => None,
+                        };
+                        Some(SavedWindow {
+                            hwnd: app.hwnd,
+                            exe: app.exe.clone(),
+                            class: app.class.clone(),
+                            title: app.title.clone(),
+                            monitor: item.monitor,
+                            floating,
+                            rect,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+        Session { workspaces }
+    }
+    /// every managed window that is off screen goes back to where it was
+    /// parked from, so nothing is lost when the manager goes away
+    pub fn restore_parked_windows(&self) {
+        let put_back = |hwnd: Hwnd, monitor: Option<usize>, y: Option<i32>| {
+            if self.backend.is_minimized(hwnd) {
+                return;
+            }
+            let (size, position) = self.backend.get_rect(hwnd);
+            let rect = LayoutRect::new(position.x, position.y, size.width, size.height);
+            if let Some(to) = rescue::on_screen_position(rect, &self.monitors, monitor, y) {
+                self.backend.set_position(hwnd, to.x, to.y);
+            }
+        };
+        let guard = self.user_widgets.lock();
+        for item in guard.workspaces.iter().flat_map(|ws| ws.hwnds.iter()) {
+            let y = item
+                .parked_position
+                .map(|y| y.max(self.get_statusbar_height(item.monitor)));
+            put_back(item.hwnd, Some(item.monitor), y);
+        }
+        for hwnd in self.scratchpads.hidden() {
+            put_back(hwnd, None, None);
+        }
+    }
+    /// visible rect of `app` when its workspace is showing, parked or not
+    fn shown_rect(&self, app: &AppInfo, item: &HwndItem) -> LayoutRect {
+        match item.parked_position {
+            Some(y) if app.position.y <= layout::PARKED_Y => self.visible_rect(&AppInfo {
+                position: AppPosition::new(app.position.x, y),
+                ..app.clone()
+            }),
+            _ => self.visible_rect(app),
+        }
+    }
+    fn workspace_info_for(&self, hwnd: Hwnd) -> Option<WorkspaceInfo> {
+        self.get_all_workspaces()
+            .into_iter()
+            .find(|ws| ws.windows.iter().any(|h| h.hwnd == hwnd))
     }
     pub fn reset_position(&self) -> Result<()> {
         for ws in self.user_widgets.lock().workspaces.iter_mut() {
@@ -417,8 +1021,8 @@ impl OverlayHandler {
                     .apps
                     .get(&hwnd_item.hwnd)
                     .ok_or(anyhow::anyhow!("can't find app"))?;
-                win_api::set_app_position(
-                    hwnd!(ai.hwnd),
+                self.backend.set_position(
+                    ai.hwnd,
                     ai.position.x,
                     self.get_statusbar_height(hwnd_item.monitor),
                 );
@@ -427,41 +1031,387 @@ impl OverlayHandler {
         Ok(())
     }
 
-    pub fn go_to_workspace(&self, direction: &CycleDirection) {
-        let mut userwidget = self.user_widgets.lock();
-        let active_monitor = self.get_active_monitor();
-        let workspace_count = userwidget.workspaces.len();
+    //==============================================================================//
+    // tag         : LAYOUT
+    // description : automatic arrangement of the active workspace per monitor
+    //==============================================================================//
+    pub fn apply_layout(&self, workspace_index: usize, monitor: usize) -> Option<()> {
+        let usable = layout::usable_area(
+            self.monitors.get(monitor)?,
+            self.get_statusbar_height(monitor),
+        );
+        let (targets, easing, gaps, area) = {
+            let mut guard = self.user_widgets.lock();
+            if guard.get_active_workspace_for_monitor(monitor) != workspace_index {
+                return None;
+            }
+            let ws = guard.workspaces.get_mut(workspace_index)?;
+            let gaps = self.gaps.resolve(monitor, &ws.text);
+            let area = gaps.shrink(usable);
+            let hwnds = self.tiled_hwnds(ws, monitor);
+            let (targets, easing) = match ws.layout {
+                LayoutKind::Floating => return None,
+                LayoutKind::Bsp => {
+                    let tree = ws.bsp.entry(monitor).or_default();
+                    tree.sync(&hwnds);
+                    (tree.layout(area), animation::AnimationEasing::EaseOutQuart)
+                }
+                LayoutKind::MasterStack => (
+                    ws.master_stack.layout(area, &hwnds),
+                    animation::AnimationEasing::EaseOutQuart,
+                ),
+                LayoutKind::Scrolling => {
+                    let strip = ws
+                        .columns
+                        .entry(monitor)
+                        .or_insert_with(|| ScrollingStrip::new(self.column_width));
+                    strip.sync(&hwnds);
+                    // viewport scroll
+                    (strip.layout(area), animation::AnimationEasing::EaseOutCubic)
+                }
+                LayoutKind::Monocle => (
+                    ws.monocle.entry(monitor).or_default().layout(area, &hwnds),
+                    animation::AnimationEasing::EaseOutQuart,
+                ),
+            };
+            (targets, easing, gaps, area)
+        };
+        for (hwnd, rect) in targets {
+            self.move_app_to_rect(hwnd, gaps.inset(rect, area), easing.clone());
+        }
+        Some(())
+    }
+    /// windows of `ws` on `monitor` that take part in the layout
+    fn tiled_hwnds(&self, ws: &Workspace, monitor: usize) -> Vec<Hwnd> {
+        ws.hwnds_on_monitor(monitor)
+            .into_iter()
+            .filter(|hwnd| !self.floating_apps.contains(hwnd))
+            .collect()
+    }
+    fn with_active_workspace<R>(&self, f: impl FnOnce(&mut Workspace, usize) -> R) -> Option<R> {
         let monitor = self.get_active_monitor();
-        let statusbar_height = self.get_statusbar_height(monitor);
-
-        if let Some(active_workspace) = userwidget
-            .active_workspace_per_monitor
-            .get_mut(active_monitor)
+        let (index, result) = {
+            let mut guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(monitor);
+            let ws = guard.workspaces.get_mut(index)?;
+            (index, f(ws, monitor))
+        };
+        self.mark_session_dirty();
+        self.apply_layout(index, monitor);
+        Some(result)
+    }
+    pub fn set_layout(&self, kind: LayoutKind) -> Option<()> {
+        self.with_active_workspace(|ws, _| ws.layout = kind)
+    }
+    pub fn update_master_stack(&self, f: impl FnOnce(&mut MasterStack)) -> Option<()> {
+        self.with_active_workspace(|ws, _| f(&mut ws.master_stack))
+    }
+    pub fn promote_to_master(&self) -> Option<()> {
+        let active = self.current_active_app?;
+        self.with_active_workspace(|ws, _| {
+            master_stack::promote(&mut ws.hwnds, |h| h.hwnd == active)
+        })
+        .filter(|promoted| *promoted)
+        .map(|_| ())
+    }
+    /// runs `f` on the strip of the active scrolling workspace, and focuses
+    /// whatever column ends up focused when `f` changed something
+    pub fn update_scrolling_strip(
+        &self,
+        f: impl FnOnce(&mut ScrollingStrip) -> bool,
+    ) -> Option<()> {
+        let focused = self.with_active_workspace(|ws, monitor| {
+            if ws.layout != LayoutKind::Scrolling {
+                return None;
+            }
+            let hwnds = self.tiled_hwnds(ws, monitor);
+            let strip = ws
+                .columns
+                .entry(monitor)
+                .or_insert_with(|| ScrollingStrip::new(self.column_width));
+            strip.sync(&hwnds);
+            if let Some(active) = self.current_active_app {
+                strip.focus_hwnd(active);
+            }
+            if f(strip) { strip.focused_hwnd() } else { None }
+        })??;
+        self.focus_app(focused);
+        Some(())
+    }
+    /// monocle workspaces rotate the shown window, others just move focus
+    /// through the workspace windows in order
+    pub fn cycle_active_app(&self, direction: &CycleDirection) -> Option<()> {
+        let active = self.current_active_app;
+        let next = self.with_active_workspace(|ws, monitor| {
+            if ws.layout == LayoutKind::Monocle {
+                let hwnds = self.tiled_hwnds(ws, monitor);
+                return ws
+                    .monocle
+                    .entry(monitor)
+                    .or_default()
+                    .cycle(&hwnds, direction);
+            }
+            let hwnds = ws.hwnds_on_monitor(monitor);
+            if hwnds.is_empty() {
+                return None;
+            }
+            let next = active
+                .and_then(|a| hwnds.iter().position(|h| *h == a))
+                .map(|i| direction.cycle(i, hwnds.len()))
+                .unwrap_or(0);
+            hwnds.get(next).copied()
+        })??;
+        self.focus_app(next);
+        self.user_widgets.lock().refresh_statusbar();
+        Some(())
+    }
+    fn follow_focus(&self, hwnd: Hwnd) -> Option<()> {
+        let monitor = self.monitor_index_for(hwnd);
+        let index = {
+            let mut guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(monitor);
+            let ws = guard.workspaces.get_mut(index)?;
+            match ws.layout {
+                LayoutKind::Scrolling => {
+                    let strip = ws.columns.get_mut(&monitor)?;
+                    if strip.focused_hwnd() == Some(hwnd) || !strip.focus_hwnd(hwnd) {
+                        return None;
+                    }
+                }
+                LayoutKind::Monocle => {
+                    let monocle = ws.monocle.get_mut(&monitor)?;
+                    if monocle.focused == Some(hwnd) || !ws.hwnds.iter().any(|h| h.hwnd == hwnd) {
+                        return None;
+                    }
+                    monocle.focus(hwnd);
+                }
+                _ => return None,
+            }
+            guard.refresh_statusbar();
+            index
+        };
+        self.apply_layout(index, monitor)
+    }
+    //==============================================================================//
+    // tag         : DIRECTIONAL
+    // description : picking windows by where they are on screen
+    //==============================================================================//
+    /// windows of the workspace showing on `monitor` that are actually on screen
+    fn visible_rects(&self, monitor: usize) -> Vec<(Hwnd, LayoutRect)> {
+        let Some(screen) = self.monitors.get(monitor).map(LayoutRect::from) else {
+            return vec![];
+        };
+        let guard = self.user_widgets.lock();
+        let index = guard.get_active_workspace_for_monitor(monitor);
+        let Some(ws) = guard.workspaces.get(index) else {
+            return vec![];
+        };
+        ws.hwnds_on_monitor(monitor)
+            .into_iter()
+            .filter_map(|hwnd| Some((hwnd, LayoutRect::from(self.apps.get(&hwnd)?))))
+            .filter(|(_, rect)| rect.intersects(&screen))
+            .collect()
+    }
+    /// nearest window from `hwnd` in `direction`, moves on to the next monitor
+    /// that way when there is nothing left on the current one
+    fn neighbour_in_direction(&self, hwnd: Hwnd, direction: &Direction) -> Option<Hwnd> {
+        let from = LayoutRect::from(self.apps.get(&hwnd)?);
+        let monitor = self.monitor_index_for(hwnd);
+        let same_monitor = self
+            .visible_rects(monitor)
+            .into_iter()
+            .filter(|(h, _)| *h != hwnd);
+        if let Some(found) = neighbour::nearest(from, same_monitor, direction) {
+            return Some(found);
+        }
+        let other_monitors = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != monitor)
+            .map(|(i, m)| (i, LayoutRect::from(m)));
+        let next_monitor = neighbour::nearest(
+            LayoutRect::from(self.monitors.get(monitor)?),
+            other_monitors,
+            direction,
+        )?;
+        neighbour::nearest(from, self.visible_rects(next_monitor), direction)
+    }
+    pub fn focus_direction(&self, direction: &Direction) -> Option<()> {
+        let active = self.current_active_app?;
+        let next = self.neighbour_in_direction(active, direction)?;
+        self.focus_app(next);
+        Some(())
+    }
+    /// trade places with the nearest window in `direction`: the workspace slots
+    /// (and with them the tiling position) and the on-screen rects. tiled
+    /// workspaces are laid out again, floating windows take the other's rect
+    pub fn swap_direction(&mut self, direction: &Direction) -> Option<()> {
+        let active = self.current_active_app?;
+        let other = self.neighbour_in_direction(active, direction)?;
+        let active_rect = self
+            .apps
+            .get(&active)
+            .map(|a| (a.position, a.size.clone()))?;
+        let other_rect = self
+            .apps
+            .get(&other)
+            .map(|a| (a.position, a.size.clone()))?;
+        // window, workspace and monitor it ends up on, and if a layout places it there
+        let mut placed = Vec::new();
         {
-            *active_workspace = match direction {
-                CycleDirection::Prev => (*active_workspace + workspace_count - 1) % workspace_count,
-                CycleDirection::Next => (*active_workspace + 1) % workspace_count,
+            let mut guard = self.user_widgets.lock();
+            let mut slots = guard
+                .workspaces
+                .iter_mut()
+                .enumerate()
+                .flat_map(|(index, ws)| {
+                    let tiled = ws.layout != LayoutKind::Floating;
+                    ws.hwnds.iter_mut().map(move |item| (index, tiled, item))
+                })
+                .filter(|(_, _, item)| item.hwnd == active || item.hwnd == other);
+            // slots keep their monitor and parking spot, only the window moves
+            if let (Some((ia, ta, a)), Some((ib, tb, b))) = (slots.next(), slots.next()) {
+                std::mem::swap(&mut a.hwnd, &mut b.hwnd);
+                std::mem::swap(&mut a.app_name, &mut b.app_name);
+                placed.push((a.hwnd, ia, a.monitor, ta));
+                placed.push((b.hwnd, ib, b.monitor, tb));
+            }
+            for ws in guard.workspaces.iter_mut() {
+                for tree in ws.bsp.values_mut() {
+                    tree.swap(active, other);
+                }
+                for strip in ws.columns.values_mut() {
+                    strip.swap(active, other);
+                }
+            }
+        }
+        let mut layouts = Vec::new();
+        for (hwnd, (position, size)) in [(active, other_rect), (other, active_rect)] {
+            let tiled = placed
+                .iter()
+                .find(|(h, ..)| *h == hwnd)
+                .filter(|(.., tiled)| *tiled && !self.floating_apps.contains(&hwnd));
+            if let Some(&(_, workspace, monitor, _)) = tiled {
+                if !layouts.contains(&(workspace, monitor)) {
+                    layouts.push((workspace, monitor));
+                }
+                continue;
+            }
+            let Some(app) = self.apps.get_mut(&hwnd) else {
+                continue;
             };
+            self.backend.animate_window(
+                hwnd,
+                app.position,
+                position,
+                app.size.clone(),
+                size.clone(),
+                animation::AnimationEasing::EaseOutQuart,
+            );
+            app.position = position;
+            app.size = size;
         }
-        let active_workspace = userwidget
-            .active_workspace_per_monitor
-            .get(active_monitor)
+        self.mark_session_dirty();
+        for (workspace, monitor) in layouts {
+            self.apply_layout(workspace, monitor);
+        }
+        Some(())
+    }
+
+    pub fn apply_active_layouts(&self) {
+        for monitor in 0..self.monitors.len() {
+            let active = self
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(monitor);
+            self.apply_layout(active, monitor);
+        }
+    }
+
+    /// the workspace called `name`, the one the monitor under the cursor has
+    /// first, or the one at index `name` on that monitor when none is
+    pub fn find_workspace(&self, name: &str) -> Option<usize> {
+        let guard = self.user_widgets.lock();
+        let on_monitor = guard.workspaces_on(self.get_active_monitor());
+        on_monitor
+            .iter()
             .copied()
-            .unwrap_or(0);
+            .find(|i| guard.workspaces[*i].text == name)
+            .or_else(|| guard.workspaces.iter().position(|ws| ws.text == name))
+            .or_else(|| on_monitor.get(name.parse::<usize>().ok()?).copied())
+    }
+    /// the workspace before or after the one showing on `monitor`, among the
+    /// ones it can show, wrapping around
+    fn workspace_in_direction(&self, direction: &CycleDirection, monitor: usize) -> Option<usize> {
+        let guard = self.user_widgets.lock();
+        let on_monitor = guard.workspaces_on(monitor);
+        let count = on_monitor.len();
+        if count == 0 {
+            return None;
+        }
+        let active = guard.get_active_workspace_for_monitor(monitor);
+        let current = on_monitor.iter().position(|i| *i == active).unwrap_or(0);
+        Some(match direction {
+            CycleDirection::Prev => on_monitor[(current + count - 1) % count],
+            CycleDirection::Next => on_monitor[(current + 1) % count],
+        })
+    }
+    pub fn go_to_workspace(&mut self, direction: &CycleDirection) -> Option<()> {
+        let monitor = self.get_active_monitor();
+        let index = self.workspace_in_direction(direction, monitor)?;
+        self.activate_workspace(index, monitor)
+    }
+    /// the `index`th workspace the monitor under the cursor can show
+    pub fn go_to_workspace_index(&mut self, index: usize) -> Option<()> {
+        let monitor = self.get_active_monitor();
+        let index = *self.user_widgets.lock().workspaces_on(monitor).get(index)?;
+        self.activate_workspace(index, monitor)
+    }
+    /// workspace `index` on the monitor it belongs to, the one under the
+    /// cursor when every monitor can show it
+    pub fn show_workspace(&mut self, index: usize) -> Option<()> {
+        let owner = self.user_widgets.lock().workspaces.get(index)?.monitor;
+        let monitor = owner.unwrap_or_else(|| self.get_active_monitor());
+        self.activate_workspace(index, monitor)
+    }
+    /// back to whatever the monitor under the cursor showed before, twice
+    /// comes back here
+    pub fn go_to_previous_workspace(&mut self) -> Option<()> {
+        let monitor = self.get_active_monitor();
+        let index = self.user_widgets.lock().previous_workspace(monitor)?;
+        self.activate_workspace(index, monitor)
+    }
+    /// shows workspace `index` on `monitor`, windows of the one showing there
+    /// before go off screen. `None` when there is no such workspace or monitor,
+    /// or it belongs to another monitor
+    pub fn activate_workspace(&mut self, index: usize, monitor: usize) -> Option<()> {
+        let mut userwidget = self.user_widgets.lock();
+        if index >= userwidget.workspaces.len() {
+            return None;
+        }
+        userwidget.set_active_workspace(monitor, index)?;
+        let active_workspace = index;
+        let statusbar_height = self.get_statusbar_height(monitor);
         // Update app position
         for (wi, workspace) in userwidget.workspaces.iter_mut().enumerate() {
             let is_active = wi == active_workspace;
+            // tiled workspaces are put back by apply_layout
+            let tiled = workspace.layout != LayoutKind::Floating;
 
             for hitem in workspace.hwnds.iter_mut() {
                 if hitem.monitor != monitor {
                     continue;
                 }
+                let tiled = tiled && !self.floating_apps.contains(&hitem.hwnd);
 
                 if let Some(appinfo) = self.apps.get(&hitem.hwnd) {
                     if is_active {
                         if let Some(parked_pos) = hitem.parked_position {
-                            animation::animate_position(
-                                appinfo.hwnd,
+                            if tiled {
+                                continue;
+                            }
+                            self.backend.animate_position(
                                 appinfo,
                                 AppPosition {
                                     x: appinfo.position.x,
@@ -478,12 +1428,11 @@ impl OverlayHandler {
                             hitem.parked_position = Some(appinfo.position.y.max(statusbar_height));
                         }
                     } else if hitem.parked_position.is_some() {
-                        animation::animate_position(
-                            appinfo.hwnd,
+                        self.backend.animate_position(
                             appinfo,
                             AppPosition {
                                 x: appinfo.position.x,
-                                y: -2000,
+                                y: layout::PARKED_Y,
                             },
                             animation::AnimationEasing::EaseInOutCirc,
                         );
@@ -494,45 +1443,347 @@ impl OverlayHandler {
         }
 
         userwidget.refresh_statusbar();
+        drop(userwidget);
+        self.apply_layout(active_workspace, monitor);
+        self.refocus_workspace(active_workspace, monitor);
+        self.publish_workspace(monitor, active_workspace);
+        Some(())
     }
-    pub fn create_workspace(&self, title: &str, monitor_index: usize) {
-        todo!()
+    fn publish_workspace(&self, monitor: usize, index: usize) {
+        events::publish(EventKind::Workspace, || Event::Workspace {
+            monitor,
+            workspace: self.get_all_workspaces().into_iter().nth(index),
+        });
     }
-    pub fn move_active_to_workspace(&mut self, workspace: &CycleDirection) -> anyhow::Result<()> {
-        let (workspace_index, hwnd, exe, moni_index, count, current) = {
-            let props = self.get_props().ok_or(anyhow!("Cant find app"))?;
-            let (count, current) = {
-                let w = self.user_widgets.lock();
-                let count = w.workspaces.len();
-                let current = w.get_active_workspace_for_monitor(props.monitor.index);
-                (count, current)
-            };
-            let workspace_index = match workspace {
-                CycleDirection::Prev => (current + count - 1) % count,
-                CycleDirection::Next => (current + 1) % count,
+    /// focus and the border go back to the window that had them the last time
+    /// workspace `index` was showing on `monitor`
+    fn refocus_workspace(&mut self, index: usize, monitor: usize) -> Option<()> {
+        let (hwnd, parked) = {
+            let guard = self.user_widgets.lock();
+            let ws = guard.workspaces.get(index)?;
+            let hwnd = ws.last_focused(monitor)?;
+            let item = ws.hwnds.iter().find(|h| h.hwnd == hwnd)?;
+            (hwnd, item.parked_position)
+        };
+        let mut app = self.apps.get(&hwnd)?.clone();
+        self.current_active_app = Some(hwnd);
+        self.focus_app(hwnd);
+        // still on its way back from off screen
+        if let Some(y) = parked.filter(|_| app.position.y <= layout::PARKED_Y) {
+            app.position.y = y.max(self.get_statusbar_height(monitor));
+        }
+        self.update_border(&app)
+    }
+    /// feeds the focus history of the workspace holding `hwnd`
+    fn remember_focus(&self, hwnd: Hwnd) {
+        let mut guard = self.user_widgets.lock();
+        if let Some(ws) = guard
+            .workspaces
+            .iter_mut()
+            .find(|ws| ws.hwnds.iter().any(|h| h.hwnd == hwnd))
+        {
+            ws.remember_focus(hwnd);
+        }
+    }
+    /// hand the active app over to the workspace showing on another monitor,
+    /// floating workspaces get the window at the same relative place and size
+    pub fn move_to_monitor(&mut self, target: &MonitorTarget) -> Option<()> {
+        let hwnd = self.current_active_app?;
+        let from = self.monitor_index_for(hwnd);
+        let to = target.resolve(from, self.monitors.len())?;
+        let (workspace_index, floating) = {
+            let guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(to);
+            let ws = guard.workspaces.get(index)?;
+            (index, ws.layout == LayoutKind::Floating)
+        };
+        let exe = self.apps.get(&hwnd)?.exe.clone();
+        if floating {
+            let app = self.apps.get(&hwnd)?;
+            let rect = self.rect_on_monitor(app, from, to)?;
+            let (position, size) =
+                self.animate_to_rect(app, rect, animation::AnimationEasing::EaseOutQuart);
+            // border and parking follow where the window is going, not where it was
+            let app = self.apps.get_mut(&hwnd)?;
+            app.position = position;
+            app.size = size;
+        }
+        self.assign_app_to_workspace(workspace_index, hwnd, &exe, to);
+        if let Some(app) = self.apps.get(&hwnd) {
+            self.update_border(app);
+        }
+        self.user_widgets.lock().refresh_statusbar();
+        Some(())
+    }
+    //==============================================================================//
+    // tag         : DYNAMIC WORKSPACES
+    // description : workspaces made, renamed, dropped and moved at runtime
+    //==============================================================================//
+    /// a new workspace called `title` at the end of the list, shown on
+    /// `monitor_index` right away. it belongs to that monitor when monitors
+    /// have their own workspaces
+    pub fn create_workspace(&mut self, title: &str, monitor_index: usize) -> Result<usize> {
+        let title = title.trim();
+        let index = {
+            let mut guard = self.user_widgets.lock();
+            guard.check_workspace_name(title, monitor_index)?;
+            if guard
+                .active_workspace_per_monitor
+                .get(monitor_index)
+                .is_none()
+            {
+                bail!("no monitor {monitor_index}");
+            }
+            let per_monitor = guard.workspaces.iter().any(|ws| ws.monitor.is_some());
+            guard.add_workspace(Workspace {
+                text: title.to_string(),
+                active: false,
+                monitor: per_monitor.then_some(monitor_index),
+                ..self.workspace_template.clone()
+            })
+        };
+        self.mark_session_dirty();
+        self.activate_workspace(index, monitor_index);
+        Ok(index)
+    }
+    pub fn rename_active_workspace(&mut self, name: &str) -> Result<()> {
+        let name = name.trim();
+        let monitor = self.get_active_monitor();
+        let index = {
+            let mut guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(monitor);
+            if guard
+                .workspaces
+                .get(index)
+                .is_some_and(|ws| ws.text == name)
+            {
+                return Ok(());
+            }
+            guard.check_workspace_name(name, monitor)?;
+            let ws = guard
+                .workspaces
+                .get_mut(index)
+                .ok_or(anyhow!("no workspace {index}"))?;
+            ws.text = name.to_string();
+            guard.refresh_statusbar();
+            index
+        };
+        self.mark_session_dirty();
+        self.publish_workspace(monitor, index);
+        Ok(())
+    }
+    /// its windows move over to the workspace before it, which takes its
+    /// place on every monitor that was showing it
+    pub fn delete_active_workspace(&mut self) -> Result<()> {
+        let monitor = self.get_active_monitor();
+        let (into, showing) = {
+            let mut guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(monitor);
+            let into = guard
+                .remove_workspace(index)
+                .ok_or(anyhow!("the last workspace can't be deleted"))?;
+            let showing: Vec<usize> = (0..self.monitors.len())
+                .filter(|m| guard.get_active_workspace_for_monitor(*m) == into)
+                .collect();
+            (into, showing)
+        };
+        self.mark_session_dirty();
+        for monitor in showing {
+            self.activate_workspace(into, monitor);
+        }
+        Ok(())
+    }
+    /// swaps the active workspace with the one before or after it among the
+    /// ones its monitor can show, no wrapping
+    pub fn move_active_workspace(&mut self, direction: &CycleDirection) -> Option<()> {
+        let monitor = self.get_active_monitor();
+        let index = {
+            let mut guard = self.user_widgets.lock();
+            let index = guard.get_active_workspace_for_monitor(monitor);
+            let on_monitor = guard.workspaces_on(monitor);
+            let position = on_monitor.iter().position(|i| *i == index)?;
+            let target = match direction {
+                CycleDirection::Prev => on_monitor[position.checked_sub(1)?],
+                CycleDirection::Next => *on_monitor.get(position + 1)?,
             };
+            guard.swap_workspaces(index, target)?;
+            guard.refresh_statusbar();
+            target
+        };
+        self.mark_session_dirty();
+        self.publish_workspace(monitor, index);
+        Some(())
+    }
+    pub fn move_active_to_workspace(
+        &mut self,
+        workspace: &CycleDirection,
+        follow: bool,
+    ) -> anyhow::Result<()> {
+        let monitor = self
+            .get_props()
+            .ok_or(anyhow!("Cant find app"))?
+            .monitor
+            .index;
+        let index = self
+            .workspace_in_direction(workspace, monitor)
+            .ok_or(anyhow!("there are no workspaces"))?;
+        self.move_active_to_workspace_index(index, follow)
+    }
+    /// hands the active app to workspace `index` on its monitor. `follow` shows
+    /// that workspace too, otherwise the app goes off screen with the rest of it
+    pub fn move_active_to_workspace_index(
+        &mut self,
+        index: usize,
+        follow: bool,
+    ) -> anyhow::Result<()> {
+        let (hwnd, exe, monitor) = {
+            let props = self.get_props().ok_or(anyhow!("Cant find app"))?;
             (
-                workspace_index,
                 props.active_hwnd,
                 props.app.exe.clone(),
                 props.monitor.index,
-                count,
-                current,
             )
         };
-        self.assign_app_to_workspace(workspace_index, hwnd, &exe, moni_index);
-        self.go_to_workspace(workspace);
+        let active = {
+            let guard = self.user_widgets.lock();
+            match guard.workspaces.get(index) {
+                None => anyhow::bail!("no workspace {index}"),
+                Some(ws) if !ws.belongs_to(monitor) => {
+                    anyhow::bail!("workspace `{}` belongs to another monitor", ws.text)
+                }
+                Some(_) => guard.get_active_workspace_for_monitor(monitor),
+            }
+        };
+        self.assign_app_to_workspace(index, hwnd, &exe, monitor);
+        if follow {
+            self.activate_workspace(index, monitor);
+        } else if index != active {
+            if let Some(mut app) = self.apps.get(&hwnd).cloned() {
+                self.park_app(&mut app, monitor);
+                self.apps.insert(hwnd, app);
+            }
+        }
         Ok(())
     }
 
-    pub fn arrange_workspaces(&self) {
-        self.reorder_app_pos_in_workspace();
+    //==============================================================================//
+    // tag         : SCRATCHPAD
+    // description : windows on no workspace, called up and sent away by name
+    //==============================================================================//
+    /// shows scratchpad `name` on the monitor under the cursor, hides it when
+    /// it is showing there already. launched when it has no window, it shows
+    /// up as soon as that opens
+    pub fn toggle_scratchpad(&mut self, name: &str) -> Result<()> {
+        let Some(hwnd) = self.scratchpads.get(name).and_then(|pad| pad.hwnd) else {
+            let command = self
+                .scratchpads
+                .get(name)
+                .and_then(|pad| pad.command())
+                .ok_or(anyhow!(
+                    "no window for scratchpad `{name}` and nothing to launch"
+                ))?
+                .to_string();
+            self.backend.launch(&command)?;
+            self.scratchpads.set_pending(name);
+            return Ok(());
+        };
+        let monitor = self.get_active_monitor();
+        let mut app = self
+            .apps
+            .get(&hwnd)
+            .cloned()
+            .ok_or(anyhow!("Cant find app {hwnd}"))?;
+        if self.scratchpads.get(name).and_then(|pad| pad.shown_on) == Some(monitor) {
+            self.hide_scratchpad(&mut app, name, true);
+        } else {
+            self.show_scratchpad(&mut app, name, monitor)
+                .ok_or(anyhow!("no monitor {monitor}"))?;
+        }
+        self.apps.insert(hwnd, app);
+        Ok(())
+    }
+    /// the active app becomes scratchpad `name` and goes off screen
+    pub fn mark_scratchpad(&mut self, name: &str) -> Result<()> {
+        let hwnd = self.current_active_app.ok_or(anyhow!("Cant find app"))?;
+        if let Some(other) = self.scratchpads.name_of(hwnd) {
+            bail!("it already is scratchpad `{other}`");
+        }
+        let mut app = self
+            .apps
+            .get(&hwnd)
+            .cloned()
+            .ok_or(anyhow!("Cant find app {hwnd}"))?;
+        self.adopt_scratchpad(&mut app, name)
+            .ok_or(anyhow!("scratchpad `{name}` already has a window"))?;
+        self.apps.insert(hwnd, app);
+        Ok(())
+    }
+    /// `app` leaves its workspace for scratchpad `name`, `None` when that has
+    /// a window already
+    fn adopt_scratchpad(&mut self, app: &mut AppInfo, name: &str) -> Option<()> {
+        let show = self.scratchpads.take(name, app.hwnd, &app.exe_path)?;
+        self.unassign_app(app.hwnd);
+        self.floating_apps.remove(&app.hwnd);
+        self.float_top_most.remove(&app.hwnd);
+        self.floating_rects.remove(&app.hwnd);
+        if show {
+            self.show_scratchpad(app, name, self.get_active_monitor());
+        } else {
+            self.hide_scratchpad(app, name, false);
+        }
+        Some(())
     }
-    fn debug_app(&self, ws: usize, monitor: usize, app: &AppInfo) {
-        println!(
-            "{} {:<3} {:<20} {} {}",
-            ws, monitor, app.exe, app.position, app.size
+    /// centred on `monitor` above everything, with focus
+    fn show_scratchpad(&mut self, app: &mut AppInfo, name: &str, monitor: usize) -> Option<()> {
+        let pad = self.scratchpads.get(name)?;
+        let area = layout::usable_area(
+            self.monitors.get(monitor)?,
+            self.get_statusbar_height(monitor),
         );
+        let rect = scratchpad::centered_rect(area, pad.width, pad.height);
+        let (position, size) =
+            self.animate_to_rect(app, rect, animation::AnimationEasing::EaseOutQuart);
+        app.position = position;
+        app.size = size;
+        self.scratchpads.get_mut(name)?.shown_on = Some(monitor);
+        self.set_top_most(app.hwnd, true);
+        self.current_active_app = Some(app.hwnd);
+        self.focus_app(app.hwnd);
+        self.update_border(app)
+    }
+    /// off screen like a workspace that isn't showing, focus goes back to the
+    /// workspace underneath
+    fn hide_scratchpad(&mut self, app: &mut AppInfo, name: &str, animate: bool) {
+        let shown_on = self
+            .scratchpads
+            .get_mut(name)
+            .and_then(|pad| pad.shown_on.take());
+        let to = AppPosition::new(app.position.x, layout::PARKED_Y);
+        if animate {
+            self.backend
+                .animate_position(app, to, animation::AnimationEasing::EaseInOutCirc);
+        } else {
+            self.backend.set_position(app.hwnd, to.x, to.y);
+        }
+        app.position = to;
+        if self.current_active_app != Some(app.hwnd) {
+            return;
+        }
+        self.current_active_app = None;
+        let monitor = shown_on.unwrap_or_else(|| self.get_active_monitor());
+        let index = self
+            .user_widgets
+            .lock()
+            .get_active_workspace_for_monitor(monitor);
+        if self.refocus_workspace(index, monitor).is_none() {
+            if let Some(ref overlay) = *self.border_overlay.lock() {
+                overlay.clear_focus();
+            }
+        }
+    }
+
+    pub fn arrange_workspaces(&self) {
+        self.reorder_app_pos_in_workspace();
     }
     fn reorder_app_pos_in_workspace(&self) {
         let mut workspaces = {
@@ -547,8 +1798,8 @@ impl OverlayHandler {
                     if is_active {
                         let statusbar_height = self.get_statusbar_height(item.monitor);
                         if let Some(parked_pos) = item.parked_position {
-                            win_api::set_app_position(
-                                hwnd!(app.hwnd),
+                            self.backend.set_position(
+                                app.hwnd,
                                 app.position.x,
                                 parked_pos.max(statusbar_height),
                             );
@@ -556,9 +1807,9 @@ impl OverlayHandler {
                             item.parked_position = Some(app.position.y.max(statusbar_height));
                         }
                     } else if item.parked_position.is_some() {
-                        win_api::set_app_position(hwnd!(app.hwnd), app.position.x, 2000);
+                        self.backend
+                            .set_position(app.hwnd, app.position.x, layout::PARKED_BELOW_Y);
                     }
-                    self.debug_app(index, item.monitor, app);
                 }
             }
         }
@@ -653,14 +1904,11 @@ impl OverlayHandler {
     pub fn spawn_widget(&self) {
         let user_widget = self.user_widgets.clone();
 
-        let hwnd = loop {
-            if let Some(&r) = self.statusbar.lock().get(0) {
-                break r;
-            }
+        while self.statusbar.lock().is_empty() {
             std::thread::sleep(std::time::Duration::from_millis(10));
-        };
+        }
         {
-            user_widget.lock().set_hwnd(Some(hwnd));
+            user_widget.lock().set_statusbars(self.statusbar.clone());
         }
         std::thread::spawn(move || {
             let mut info = SystemInfo::new();
@@ -703,3 +1951,1072 @@ impl OverlayHandler {
         });
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::{
+        backend::{BackendCall, FakeBackend},
+        config::WindowRule,
+        rules::Rule,
+    };
+
+    fn monitor(index: usize, x: i32) -> StatusbarMonitorInfo {
+        StatusbarMonitorInfo {
+            handle: index as isize,
+            index,
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            is_primary: index == 0,
+        }
+    }
+    fn app(hwnd: Hwnd, exe: &str, x: i32, y: i32) -> AppInfo {
+        AppInfo {
+            hwnd,
+            exe: exe.to_string(),
+            exe_path: format!("C:\\{exe}"),
+            size: AppSize::new(800, 600),
+            position: AppPosition::new(x, y),
+            title: exe.to_string(),
+            class: "Window".to_string(),
+            column: Column::Left,
+            size_ratio: SizeRatio {
+                width: 1.0,
+                height: 1.0,
+            },
+        }
+    }
+    // two workspaces on one monitor, `Work` holds hwnd 1, `Browsing` holds hwnd 2 (parked)
+    fn setup() -> (Arc<FakeBackend>, OverlayHandler) {
+        let backend = Arc::new(FakeBackend::new(vec![monitor(0, 0)]));
+        let mut handler = OverlayHandler::with_backend(backend.clone());
+        handler.set_monitors(backend.get_monitors());
+        for (hwnd, exe, y) in [(1, "code.exe", 100), (2, "firefox.exe", layout::PARKED_Y)] {
+            backend.add_window(hwnd, AppPosition::new(0, y), AppSize::new(800, 600), 0);
+            handler.apps.insert(hwnd, app(hwnd, exe, 0, y));
+        }
+        let mut work = HwndItem::new(1, "code.exe", 0);
+        work.parked_position = Some(100);
+        let mut browsing = HwndItem::new(2, "firefox.exe", 0);
+        browsing.parked_position = Some(200);
+        handler.user_widgets.lock().workspaces = vec![
+            Workspace::new("Work", vec![work]),
+            Workspace::new("Browsing", vec![browsing]),
+        ];
+        handler.current_active_app = Some(1);
+        (backend, handler)
+    }
+    // `setup` on monitor 0, `Chat` with hwnd 3 and `Music` on a second monitor
+    fn setup_per_monitor() -> (Arc<FakeBackend>, OverlayHandler) {
+        let (_, mut handler) = setup();
+        let backend = Arc::new(FakeBackend::new(vec![monitor(0, 0), monitor(1, 1920)]));
+        backend.add_window(1, AppPosition::new(0, 100), AppSize::new(800, 600), 0);
+        backend.add_window(3, AppPosition::new(2000, 100), AppSize::new(800, 600), 1);
+        handler.backend = backend.clone();
+        handler.apps.insert(3, app(3, "wezterm.exe", 2000, 100));
+        {
+            let mut guard = handler.user_widgets.lock();
+            for ws in guard.workspaces.iter_mut() {
+                ws.monitor = Some(0);
+            }
+            let mut chat = Workspace::new("Chat", vec![HwndItem::new(3, "wezterm.exe", 1)]);
+            chat.monitor = Some(1);
+            let mut music = Workspace::new("Music", vec![]);
+            music.monitor = Some(1);
+            guard.workspaces.extend([chat, music]);
+        }
+        handler.set_monitors(backend.get_monitors());
+        (backend, handler)
+    }
+    fn active_workspaces(handler: &OverlayHandler) -> Vec<usize> {
+        handler
+            .user_widgets
+            .lock()
+            .active_workspace_per_monitor
+            .clone()
+    }
+    // `setup` with a 2560x1440 monitor right of the first one
+    fn setup_bigger_second_monitor() -> (Arc<FakeBackend>, OverlayHandler) {
+        let (_, mut handler) = setup();
+        let second = StatusbarMonitorInfo {
+            width: 2560,
+            height: 1440,
+            ..monitor(1, 1920)
+        };
+        let backend = Arc::new(FakeBackend::new(vec![monitor(0, 0), second]));
+        backend.add_window(1, AppPosition::new(0, 100), AppSize::new(800, 600), 0);
+        handler.backend = backend.clone();
+        handler.set_monitors(backend.get_monitors());
+        (backend, handler)
+    }
+    // `setup` with wezterm (hwnd 3) tiled next to code on a Bsp workspace 0
+    fn setup_bsp() -> (Arc<FakeBackend>, OverlayHandler) {
+        let (backend, mut handler) = setup();
+        handler.user_widgets.lock().workspaces[0].layout = LayoutKind::Bsp;
+        backend.add_window(3, AppPosition::new(0, 0), AppSize::new(800, 600), 0);
+        handler.apps.insert(3, app(3, "wezterm.exe", 0, 0));
+        handler.assign_app_to_workspace(0, 3, "wezterm.exe", 0);
+        backend.clear_calls();
+        (backend, handler)
+    }
+    fn rect_of(backend: &FakeBackend, hwnd: Hwnd) -> Option<(AppPosition, AppSize)> {
+        backend.window(hwnd).map(|w| (w.position, w.size))
+    }
+    // notepad is ignored, wezterm goes to the right half of `Browsing`, top most
+    fn setup_rules() -> (Arc<FakeBackend>, OverlayHandler) {
+        let (backend, mut handler) = setup();
+        let grid = [(0.0, 0.0, 1.0, 1.0), (0.5, 0.0, 0.5, 1.0)];
+        let rule = |rule: WindowRule| Rule::from_config(&rule, &grid).unwrap();
+        handler.rules = Rules::new(vec![
+            rule(WindowRule {
+                exe: Some("notepad*".into()),
+                ignore: Some(true),
+                ..Default::default()
+            }),
+            rule(WindowRule {
+                class: Some("/^WezTerm/".into()),
+                workspace: Some("Browsing".into()),
+                grid: Some(1),
+                top_most: Some(true),
+                ..Default::default()
+            }),
+        ]);
+        for hwnd in [3, 4] {
+            backend.add_window(hwnd, AppPosition::new(0, 100), AppSize::new(800, 600), 0);
+        }
+        (backend, handler)
+    }
+    fn wezterm() -> AppInfo {
+        AppInfo {
+            class: "WezTermWindow".into(),
+            ..app(3, "wezterm.exe", 0, 100)
+        }
+    }
+    // `setup` with hwnd 3 taken as scratchpad `term` by a rule
+    fn setup_scratchpad() -> (Arc<FakeBackend>, OverlayHandler) {
+        let (backend, mut handler) = setup();
+        handler.rules = Rules::new(vec![
+            Rule::from_config(
+                &WindowRule {
+                    exe: Some("wezterm.exe".into()),
+                    scratchpad: Some("term".into()),
+                    ..Default::default()
+                },
+                &[],
+            )
+            .unwrap(),
+        ]);
+        backend.add_window(3, AppPosition::new(100, 100), AppSize::new(800, 600), 0);
+        handler.update_apps(app(3, "wezterm.exe", 100, 100), WinEvent::ObjectShow);
+        (backend, handler)
+    }
+    // `hwnd` animated to the middle of monitor 0 at the default scratchpad size
+    fn shown_scratchpad(handler: &OverlayHandler, hwnd: Hwnd) -> BackendCall {
+        let area = layout::usable_area(&monitor(0, 0), handler.get_statusbar_height(0));
+        let rect = scratchpad::centered_rect(
+            area,
+            scratchpad::SCRATCHPAD_SIZE,
+            scratchpad::SCRATCHPAD_SIZE,
+        );
+        BackendCall::Animate {
+            hwnd,
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+    fn workspace_names(handler: &OverlayHandler) -> Vec<String> {
+        handler
+            .get_all_workspaces()
+            .into_iter()
+            .map(|ws| ws.name)
+            .collect()
+    }
+    // name of the workspace `monitor` shows
+    fn showing(handler: &OverlayHandler, monitor: usize) -> Option<String> {
+        handler
+            .get_all_workspaces()
+            .into_iter()
+            .find(|ws| ws.active_on.contains(&monitor))
+            .map(|ws| ws.name)
+    }
+    fn workspace_of(handler: &OverlayHandler, hwnd: Hwnd) -> Option<usize> {
+        handler
+            .user_widgets
+            .lock()
+            .workspaces
+            .iter()
+            .position(|ws| ws.hwnds.iter().any(|h| h.hwnd == hwnd))
+    }
+
+    #[test]
+    fn snapshot_has_everything_for_a_bug_report() {
+        let (_, mut handler) = setup();
+        handler.floating_apps.insert(2);
+        handler.apps.insert(3, app(3, "notepad.exe", 10, 10));
+        let snapshot = handler.snapshot();
+
+        assert_eq!(snapshot.active_workspace_per_monitor, vec![0]);
+        assert_eq!(snapshot.focused, Some(1));
+        assert_eq!(snapshot.floating, vec![2]);
+        assert_eq!(snapshot.workspaces[1].windows[0].parked_position, Some(200));
+        assert_eq!(snapshot.apps.len(), 3);
+
+        let text = snapshot.to_string();
+        assert!(text.starts_with("monitor 0 1920x1080+0+0 primary, showing workspace 0\n"));
+        assert!(text.contains("code.exe"));
+        assert!(text.contains(&format!(
+            "parked 200 at (0, {}) (800, 600) floating\n",
+            layout::PARKED_Y
+        )));
+        assert!(text.ends_with(
+            "not on a workspace\n  3          notepad.exe              at (10, 10) (800, 600)\n"
+        ));
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["workspaces"][0]["windows"][0]["hwnd"], 1);
+        assert_eq!(json["floating"], serde_json::json!([2]));
+    }
+
+    #[test]
+    fn session_puts_windows_back_after_a_restart() {
+        let (_, mut handler) = setup();
+        handler.floating_apps.insert(1);
+        handler
+            .floating_rects
+            .insert(1, LayoutRect::new(100, 100, 640, 480));
+        handler.user_widgets.lock().workspaces[1]
+            .master_stack
+            .master_count = 2;
+        let session = handler.session();
+        let browsing = &session.workspaces[1].windows[0];
+        // parked, saved where it shows up again
+        assert_eq!(browsing.rect, Some(LayoutRect::new(0, 200, 800, 600)));
+
+        // a fresh manager, same windows under new hwnds
+        let (backend, mut handler) = setup();
+        handler.apps.clear();
+        for ws in handler.user_widgets.lock().workspaces.iter_mut() {
+            ws.hwnds.clear();
+            ws.master_stack.master_count = 1;
+        }
+        handler.restore_session(session);
+        assert_eq!(
+            handler.user_widgets.lock().workspaces[1]
+                .master_stack
+                .master_count,
+            2
+        );
+        for (hwnd, exe) in [(11, "code.exe"), (12, "firefox.exe")] {
+            backend.add_window(hwnd, AppPosition::new(0, 0), AppSize::new(800, 600), 0);
+            handler.update_apps(app(hwnd, exe, 0, 0), WinEvent::ObjectCreate);
+        }
+        assert_eq!(workspace_of(&handler, 11), Some(0));
+        assert_eq!(workspace_of(&handler, 12), Some(1));
+        assert!(handler.floating_apps.contains(&11));
+        assert_eq!(
+            handler.floating_rects.get(&11),
+            Some(&LayoutRect::new(100, 100, 640, 480))
+        );
+        // not showing, so parked until its workspace is
+        assert_eq!(handler.apps[&12].position.y, layout::PARKED_Y);
+    }
+
+    #[test]
+    fn restore_parked_windows_brings_everything_back() {
+        let (backend, handler) = setup();
+        handler.reorder_app_pos_in_workspace();
+        backend.clear_calls();
+        handler.restore_parked_windows();
+        // only the one parked at PARKED_BELOW_Y had to move
+        assert_eq!(
+            backend.calls(),
+            vec![BackendCall::SetPosition {
+                hwnd: 2,
+                x: 0,
+                y: 200
+            }]
+        );
+    }
+
+    #[test]
+    fn go_to_workspace_parks_and_restores() {
+        let (backend, mut handler) = setup();
+        handler.go_to_workspace(&CycleDirection::Next);
+
+        assert_eq!(
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0),
+            1
+        );
+        let calls = backend.calls();
+        assert!(calls.contains(&BackendCall::Animate {
+            hwnd: 1,
+            x: 0,
+            y: layout::PARKED_Y,
+            width: 800,
+            height: 600
+        }));
+        assert!(calls.contains(&BackendCall::Animate {
+            hwnd: 2,
+            x: 0,
+            y: 200,
+            width: 800,
+            height: 600
+        }));
+    }
+
+    #[test]
+    fn go_to_workspace_wraps_around() {
+        let (_, mut handler) = setup();
+        handler.go_to_workspace(&CycleDirection::Prev);
+        assert_eq!(
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0),
+            1
+        );
+    }
+
+    #[test]
+    fn move_active_to_workspace_reassigns_and_follows() {
+        let (_, mut handler) = setup();
+        handler
+            .move_active_to_workspace(&CycleDirection::Next, true)
+            .unwrap();
+
+        assert_eq!(workspace_of(&handler, 1), Some(1));
+        assert_eq!(workspace_of(&handler, 2), Some(1));
+        assert_eq!(
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0),
+            1
+        );
+    }
+
+    #[test]
+    fn move_active_to_workspace_without_active_app() {
+        let (_, mut handler) = setup();
+        handler.current_active_app = None;
+        assert!(
+            handler
+                .move_active_to_workspace(&CycleDirection::Next, true)
+                .is_err()
+        );
+        assert_eq!(workspace_of(&handler, 1), Some(0));
+    }
+
+    #[test]
+    fn move_active_to_workspace_without_following_parks_it() {
+        let (backend, mut handler) = setup();
+        handler.move_active_to_workspace_index(1, false).unwrap();
+
+        assert_eq!(workspace_of(&handler, 1), Some(1));
+        assert_eq!(
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0),
+            0
+        );
+        assert!(backend.calls().contains(&BackendCall::SetPosition {
+            hwnd: 1,
+            x: 0,
+            y: layout::PARKED_Y
+        }));
+        // showing it later puts the window back where it was
+        handler.go_to_workspace_index(1).unwrap();
+        assert!(matches!(
+            backend.calls().last(),
+            Some(BackendCall::Animate {
+                hwnd: 1,
+                y: 100,
+                ..
+            })
+        ));
+        assert!(handler.move_active_to_workspace_index(5, false).is_err());
+    }
+
+    #[test]
+    fn workspaces_remember_focus_and_go_back_and_forth() {
+        let (backend, mut handler) = setup();
+        let active = |handler: &OverlayHandler| {
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0)
+        };
+        handler.update_apps(app(1, "code.exe", 0, 100), WinEvent::SystemForeground);
+        handler.update_apps(
+            app(2, "firefox.exe", 0, layout::PARKED_Y),
+            WinEvent::SystemForeground,
+        );
+        assert_eq!(handler.go_to_previous_workspace(), None);
+        handler.go_to_workspace_index(1).unwrap();
+        handler.go_to_workspace_index(0).unwrap();
+        assert_eq!(handler.current_active_app, Some(1));
+
+        backend.clear_calls();
+        handler.go_to_previous_workspace().unwrap();
+        assert_eq!(active(&handler), 1);
+        assert_eq!(handler.current_active_app, Some(2));
+        assert!(backend.calls().contains(&BackendCall::BringToFront(2)));
+        handler.go_to_previous_workspace().unwrap();
+        assert_eq!(active(&handler), 0);
+        assert_eq!(handler.current_active_app, Some(1));
+    }
+
+    #[test]
+    fn workspace_names_have_to_be_new_and_not_blank() {
+        let (_, mut handler) = setup();
+        assert!(handler.create_workspace("Work", 0).is_err());
+        assert!(handler.create_workspace(" ", 0).is_err());
+        assert!(handler.rename_active_workspace("Browsing").is_err());
+        assert_eq!(workspace_names(&handler), vec!["Work", "Browsing"]);
+    }
+
+    #[test]
+    fn created_workspaces_show_right_away() {
+        let (backend, mut handler) = setup();
+        assert_eq!(handler.create_workspace("Music", 0).unwrap(), 2);
+        assert_eq!(showing(&handler, 0), Some("Music".to_string()));
+        assert!(backend.calls().contains(&BackendCall::Animate {
+            hwnd: 1,
+            x: 0,
+            y: layout::PARKED_Y,
+            width: 800,
+            height: 600
+        }));
+    }
+
+    #[test]
+    fn the_active_workspace_is_renamed_and_moved_in_place() {
+        let (_, mut handler) = setup();
+        handler.go_to_workspace_index(1).unwrap();
+        handler.rename_active_workspace("Web").unwrap();
+        handler
+            .move_active_workspace(&CycleDirection::Prev)
+            .unwrap();
+        assert_eq!(workspace_names(&handler), vec!["Web", "Work"]);
+        assert_eq!(showing(&handler, 0), Some("Web".to_string()));
+        assert_eq!(workspace_of(&handler, 2), Some(0));
+        // nothing before the first one
+        assert_eq!(handler.move_active_workspace(&CycleDirection::Prev), None);
+    }
+
+    #[test]
+    fn deleted_workspaces_hand_their_windows_to_the_one_before() {
+        let (_, mut handler) = setup();
+        handler.go_to_workspace_index(1).unwrap();
+        handler.delete_active_workspace().unwrap();
+        assert_eq!(workspace_names(&handler), vec!["Work"]);
+        assert_eq!(workspace_of(&handler, 2), Some(0));
+        assert_eq!(showing(&handler, 0), Some("Work".to_string()));
+    }
+
+    #[test]
+    fn the_last_workspace_stays() {
+        let (_, mut handler) = setup();
+        handler.delete_active_workspace().unwrap();
+        assert!(handler.delete_active_workspace().is_err());
+        assert_eq!(workspace_names(&handler), vec!["Browsing"]);
+    }
+
+    #[test]
+    fn monitors_start_on_their_first_workspace() {
+        let (_, handler) = setup_per_monitor();
+        assert_eq!(active_workspaces(&handler), vec![0, 2]);
+    }
+
+    #[test]
+    fn cycling_stays_on_the_monitor_under_the_cursor() {
+        let (backend, mut handler) = setup_per_monitor();
+        backend.set_cursor_monitor(1);
+        handler.go_to_workspace(&CycleDirection::Next).unwrap();
+        assert_eq!(active_workspaces(&handler), vec![0, 3]);
+        handler.go_to_workspace(&CycleDirection::Next).unwrap();
+        assert_eq!(active_workspaces(&handler), vec![0, 2]);
+    }
+
+    #[test]
+    fn indices_count_the_workspaces_of_the_cursor_monitor() {
+        let (backend, mut handler) = setup_per_monitor();
+        backend.set_cursor_monitor(1);
+        assert_eq!(handler.find_workspace("1"), Some(3));
+        handler.go_to_workspace_index(1).unwrap();
+        assert_eq!(active_workspaces(&handler), vec![0, 3]);
+        assert!(handler.go_to_workspace_index(2).is_none());
+    }
+
+    #[test]
+    fn workspaces_of_another_monitor_stay_there() {
+        let (_, mut handler) = setup_per_monitor();
+        assert!(handler.activate_workspace(0, 1).is_none());
+        assert!(handler.move_active_to_workspace_index(2, false).is_err());
+        assert_eq!(workspace_of(&handler, 1), Some(0));
+        assert_eq!(active_workspaces(&handler), vec![0, 2]);
+    }
+
+    #[test]
+    fn a_name_shows_on_the_monitor_that_has_it() {
+        let (backend, mut handler) = setup_per_monitor();
+        backend.set_cursor_monitor(1);
+        let browsing = handler.find_workspace("Browsing").unwrap();
+        handler.show_workspace(browsing).unwrap();
+        assert_eq!(active_workspaces(&handler), vec![1, 2]);
+    }
+
+    #[test]
+    fn names_only_have_to_differ_per_monitor() {
+        let (backend, mut handler) = setup_per_monitor();
+        backend.set_cursor_monitor(1);
+        let index = handler.create_workspace("Work", 1).unwrap();
+        assert_eq!(
+            handler.user_widgets.lock().workspaces[index].monitor,
+            Some(1)
+        );
+        assert_eq!(handler.find_workspace("Work"), Some(index));
+    }
+
+    #[test]
+    fn workspaces_are_found_by_name_or_index() {
+        let (_, mut handler) = setup();
+        assert_eq!(handler.find_workspace("Browsing"), Some(1));
+        assert_eq!(handler.find_workspace("0"), Some(0));
+        assert_eq!(handler.find_workspace("2"), None);
+        assert_eq!(handler.find_workspace("Music"), None);
+        assert!(handler.activate_workspace(2, 0).is_none());
+        assert!(handler.activate_workspace(1, 3).is_none());
+        handler.activate_workspace(1, 0).unwrap();
+        assert_eq!(
+            handler
+                .user_widgets
+                .lock()
+                .get_active_workspace_for_monitor(0),
+            1
+        );
+    }
+
+    #[test]
+    fn cycle_app_on_grid_respects_statusbar() {
+        let (backend, mut handler) = setup();
+        let grid = [(0.0, 0.0, 1.0, 1.0), (0.5, 0.0, 0.5, 1.0)];
+        handler.cycle_app_on_grid(&grid);
+
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        assert_eq!(
+            backend.calls().last(),
+            Some(&BackendCall::Animate {
+                hwnd: 1,
+                x: 960,
+                y: toolbar,
+                width: 960,
+                height: 1080 - toolbar
+            })
+        );
+        handler.cycle_app_on_grid(&grid);
+        assert_eq!(handler.grid_app_position, 0);
+
+        assert_eq!(handler.move_to_grid_cell(2, &grid), None);
+        handler.move_to_grid_cell(1, &grid);
+        assert_eq!(handler.grid_app_position, 1);
+        assert!(matches!(
+            backend.calls().last(),
+            Some(&BackendCall::Animate { x: 960, .. })
+        ));
+    }
+
+    #[test]
+    fn assign_to_bsp_workspace_tiles() {
+        let (backend, mut handler) = setup();
+        handler.user_widgets.lock().workspaces[0].layout = LayoutKind::Bsp;
+        backend.add_window(3, AppPosition::new(0, 0), AppSize::new(800, 600), 0);
+        handler.apps.insert(3, app(3, "wezterm.exe", 0, 0));
+        handler.assign_app_to_workspace(0, 3, "wezterm.exe", 0);
+
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        let tiled = |hwnd| backend.window(hwnd).map(|w| (w.position, w.size));
+        assert_eq!(
+            tiled(1),
+            Some((
+                AppPosition::new(0, toolbar),
+                AppSize::new(960, 1080 - toolbar)
+            ))
+        );
+        assert_eq!(
+            tiled(3),
+            Some((
+                AppPosition::new(960, toolbar),
+                AppSize::new(960, 1080 - toolbar)
+            ))
+        );
+
+        handler.delete_app(&app(3, "wezterm.exe", 0, 0));
+        assert_eq!(tiled(1).map(|(_, size)| size.width), Some(1920));
+    }
+
+    #[test]
+    fn gaps_apply_to_tiles_and_grid() {
+        let (backend, mut handler) = setup();
+        handler.gaps.base = Gaps::new(10, 20);
+        handler.user_widgets.lock().workspaces[0].layout = LayoutKind::Bsp;
+        backend.add_window(3, AppPosition::new(0, 0), AppSize::new(800, 600), 0);
+        handler.apps.insert(3, app(3, "wezterm.exe", 0, 0));
+        handler.assign_app_to_workspace(0, 3, "wezterm.exe", 0);
+
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        let tiled = |hwnd| backend.window(hwnd).map(|w| (w.position, w.size));
+        let height = 1080 - toolbar - 20;
+        assert_eq!(
+            tiled(1),
+            Some((
+                AppPosition::new(10, toolbar + 10),
+                AppSize::new(940, height)
+            ))
+        );
+        assert_eq!(
+            tiled(3),
+            Some((
+                AppPosition::new(970, toolbar + 10),
+                AppSize::new(940, height)
+            ))
+        );
+
+        handler.update_gaps(|gaps| gaps.toggle());
+        assert_eq!(
+            tiled(1).map(|(pos, _)| pos),
+            Some(AppPosition::new(0, toolbar))
+        );
+
+        handler.update_gaps(|gaps| gaps.toggle());
+        handler.cycle_app_on_grid(&[(0.0, 0.0, 1.0, 1.0), (0.5, 0.0, 0.5, 1.0)]);
+        assert_eq!(
+            backend.calls().last(),
+            Some(&BackendCall::Animate {
+                hwnd: 1,
+                x: 960 + 10,
+                y: toolbar + 10,
+                width: 940,
+                height
+            })
+        );
+    }
+
+    #[test]
+    fn focus_direction_crosses_monitors() {
+        let (_, mut handler) = setup();
+        let backend = Arc::new(FakeBackend::new(vec![monitor(0, 0), monitor(1, 1920)]));
+        backend.add_window(1, AppPosition::new(0, 100), AppSize::new(800, 600), 0);
+        backend.add_window(3, AppPosition::new(2000, 100), AppSize::new(800, 600), 1);
+        handler.backend = backend.clone();
+        handler.set_monitors(backend.get_monitors());
+        handler.apps.insert(3, app(3, "wezterm.exe", 2000, 100));
+        handler.user_widgets.lock().workspaces[0]
+            .hwnds
+            .push(HwndItem::new(3, "wezterm.exe", 1));
+
+        // hwnd 2 is parked on the other workspace, so nothing is left or right on monitor 0
+        handler.focus_direction(&Direction::Right);
+        assert_eq!(backend.calls(), vec![BackendCall::BringToFront(3)]);
+
+        backend.clear_calls();
+        assert_eq!(handler.focus_direction(&Direction::Left), None);
+        assert_eq!(handler.focus_direction(&Direction::Down), None);
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn swap_direction_trades_rects_and_slots() {
+        let (backend, mut handler) = setup();
+        handler.user_widgets.lock().workspaces[0].layout = LayoutKind::Bsp;
+        backend.add_window(3, AppPosition::new(0, 0), AppSize::new(800, 600), 0);
+        handler.apps.insert(3, app(3, "wezterm.exe", 0, 0));
+        handler.assign_app_to_workspace(0, 3, "wezterm.exe", 0);
+        for hwnd in [1, 3] {
+            let window = backend.window(hwnd).unwrap();
+            let app = handler.apps.get_mut(&hwnd).unwrap();
+            app.position = window.position;
+            app.size = window.size;
+        }
+        backend.clear_calls();
+
+        handler.swap_direction(&Direction::Right);
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        let calls = backend.calls();
+        assert!(calls.contains(&BackendCall::Animate {
+            hwnd: 1,
+            x: 960,
+            y: toolbar,
+            width: 960,
+            height: 1080 - toolbar
+        }));
+        assert!(calls.contains(&BackendCall::Animate {
+            hwnd: 3,
+            x: 0,
+            y: toolbar,
+            width: 960,
+            height: 1080 - toolbar
+        }));
+        let guard = handler.user_widgets.lock();
+        let slots: Vec<_> = guard.workspaces[0].hwnds.iter().map(|h| h.hwnd).collect();
+        assert_eq!(slots, vec![3, 1]);
+        assert_eq!(guard.workspaces[0].bsp[&0].hwnds(), vec![3, 1]);
+        drop(guard);
+
+        // nothing further right of the right tile
+        backend.clear_calls();
+        handler.current_active_app = Some(3);
+        assert_eq!(handler.swap_direction(&Direction::Right), None);
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn swap_direction_moves_floating_windows_to_the_others_rect() {
+        let (backend, mut handler) = setup();
+        handler.user_widgets.lock().workspaces[0].layout = LayoutKind::Floating;
+        backend.add_window(3, AppPosition::new(1000, 100), AppSize::new(400, 300), 0);
+        handler.apps.insert(3, app(3, "wezterm.exe", 1000, 100));
+        handler.apps.get_mut(&3).unwrap().size = AppSize::new(400, 300);
+        handler.assign_app_to_workspace(0, 3, "wezterm.exe", 0);
+        backend.clear_calls();
+
+        handler.swap_direction(&Direction::Right);
+        assert!(backend.calls().contains(&BackendCall::Animate {
+            hwnd: 1,
+            x: 1000,
+            y: 100,
+            width: 400,
+            height: 300
+        }));
+        assert_eq!(handler.apps[&1].position, AppPosition::new(1000, 100));
+        assert_eq!(handler.apps[&3].position, AppPosition::new(0, 100));
+        assert_eq!(handler.apps[&3].size, AppSize::new(800, 600));
+    }
+
+    #[test]
+    fn move_to_monitor_scales_and_reparents() {
+        let (backend, mut handler) = setup_bigger_second_monitor();
+        handler.move_to_monitor(&MonitorTarget::Next);
+        assert!(backend.calls().contains(&BackendCall::Animate {
+            hwnd: 1,
+            x: 1920,
+            y: 96,
+            width: 1067,
+            height: 823
+        }));
+        assert_eq!(backend.window(1).map(|w| w.monitor), Some(1));
+        let guard = handler.user_widgets.lock();
+        let item = guard.workspaces[0].hwnds.iter().find(|h| h.hwnd == 1);
+        assert_eq!(item.map(|h| h.monitor), Some(1));
+    }
+
+    #[test]
+    fn move_to_monitor_needs_another_monitor() {
+        let (backend, mut handler) = setup_bigger_second_monitor();
+        backend.clear_calls();
+        // already there
+        assert_eq!(handler.move_to_monitor(&MonitorTarget::Index(0)), None);
+        assert_eq!(handler.move_to_monitor(&MonitorTarget::Index(5)), None);
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn apply_config_keeps_assigned_windows() {
+        let (_, mut handler) = setup();
+        let config = NtekConfig {
+            workspace_grid: vec![],
+            workspaces: vec!["Code".into(), "Browsing".into(), "Files".into()],
+            move_inc: 50,
+            size_inc: 50,
+            hotkeys: HashMap::new(),
+            blacklist: vec!["notepad.exe".into()],
+            size_factor: vec![1.0, 0.5],
+            layout: None,
+            master_count: None,
+            master_ratio: None,
+            column_width: None,
+            outer_gap: Some(12),
+            inner_gap: None,
+            gap_overrides: None,
+            rules: None,
+            monitor_workspaces: None,
+            scratchpads: None,
+        };
+        handler.apply_config(&config);
+
+        assert_eq!(handler.blacklist, vec!["notepad.exe".to_string()]);
+        assert_eq!(handler.gaps.base, Gaps::new(12, 0));
+        let names: Vec<_> = handler
+            .user_widgets
+            .lock()
+            .workspaces
+            .iter()
+            .map(|ws| ws.text.clone())
+            .collect();
+        assert_eq!(names, vec!["Code", "Browsing", "Files"]);
+        assert_eq!(workspace_of(&handler, 1), Some(0));
+        assert_eq!(workspace_of(&handler, 2), Some(1));
+    }
+
+    #[test]
+    fn ignored_windows_are_left_alone() {
+        let (backend, mut handler) = setup_rules();
+        handler.update_apps(app(4, "notepad.exe", 0, 100), WinEvent::ObjectShow);
+
+        assert_eq!(workspace_of(&handler, 4), None);
+        assert!(!handler.apps.contains_key(&4));
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn rules_place_new_windows() {
+        let (backend, mut handler) = setup_rules();
+        handler.update_apps(wezterm(), WinEvent::ObjectShow);
+
+        assert_eq!(workspace_of(&handler, 3), Some(1));
+        assert!(handler.top_most_apps.contains(&3));
+        // right half of the grid, then parked since `Browsing` isn't showing
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        assert_eq!(
+            backend.calls(),
+            vec![
+                BackendCall::ToggleTopMost(3),
+                BackendCall::Animate {
+                    hwnd: 3,
+                    x: 960,
+                    y: toolbar,
+                    width: 960,
+                    height: 1080 - toolbar
+                },
+                BackendCall::SetPosition {
+                    hwnd: 3,
+                    x: 960,
+                    y: layout::PARKED_Y
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn placed_windows_show_with_their_workspace() {
+        let (backend, mut handler) = setup_rules();
+        handler.update_apps(wezterm(), WinEvent::ObjectShow);
+        backend.clear_calls();
+
+        handler.go_to_workspace(&CycleDirection::Next);
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        assert!(backend.calls().contains(&BackendCall::Animate {
+            hwnd: 3,
+            x: 960,
+            y: toolbar,
+            width: 960,
+            height: 1080 - toolbar
+        }));
+    }
+
+    #[test]
+    fn rule_borders_are_resolved_when_placed_and_on_reload() {
+        let (backend, mut handler) = setup();
+        handler.rules = Rules::new(vec![
+            Rule::from_config(
+                &WindowRule {
+                    exe: Some("wezterm.exe".into()),
+                    border: Some("#8aadf4".into()),
+                    ..Default::default()
+                },
+                &[],
+            )
+            .unwrap(),
+        ]);
+        backend.add_window(3, AppPosition::new(0, 100), AppSize::new(800, 600), 0);
+        handler.update_apps(app(3, "wezterm.exe", 0, 100), WinEvent::ObjectShow);
+        assert_eq!(handler.rule_borders.get(&3), Some(&0x8aadf4));
+        assert_eq!(handler.rule_borders.get(&1), None);
+
+        // a config without the rule takes the colour away
+        let config = NtekConfig {
+            workspace_grid: vec![],
+            workspaces: vec!["Work".into(), "Browsing".into()],
+            move_inc: 50,
+            size_inc: 50,
+            hotkeys: HashMap::new(),
+            blacklist: vec![],
+            size_factor: vec![],
+            layout: None,
+            master_count: None,
+            master_ratio: None,
+            column_width: None,
+            outer_gap: None,
+            inner_gap: None,
+            gap_overrides: None,
+            rules: None,
+            monitor_workspaces: None,
+            scratchpads: None,
+        };
+        handler.apply_config(&config);
+        assert!(handler.rule_borders.is_empty());
+    }
+
+    #[test]
+    fn a_first_float_lands_in_the_middle_on_top() {
+        let (backend, mut handler) = setup_bsp();
+        handler.toggle_float();
+        assert!(handler.floating_apps.contains(&1));
+        assert!(handler.top_most_apps.contains(&1));
+        assert_eq!(
+            rect_of(&backend, 1),
+            Some((AppPosition::new(384, 240), AppSize::new(1152, 630)))
+        );
+    }
+
+    #[test]
+    fn floating_leaves_the_layout_to_the_others() {
+        let (backend, mut handler) = setup_bsp();
+        handler.toggle_float();
+        let toolbar = STATUSBAR_HEIGHT as i32;
+        assert_eq!(
+            rect_of(&backend, 3),
+            Some((
+                AppPosition::new(0, toolbar),
+                AppSize::new(1920, 1080 - toolbar)
+            ))
+        );
+    }
+
+    #[test]
+    fn un_floating_tiles_it_again_and_drops_topmost() {
+        let (backend, mut handler) = setup_bsp();
+        handler.toggle_float();
+        handler.toggle_float();
+        assert!(!handler.floating_apps.contains(&1));
+        assert!(!handler.top_most_apps.contains(&1));
+        assert_eq!(rect_of(&backend, 3).map(|(_, size)| size.width), Some(960));
+    }
+
+    #[test]
+    fn floating_again_goes_back_to_the_remembered_rect() {
+        let (backend, mut handler) = setup_bsp();
+        handler.toggle_float();
+        // dragged somewhere else, then tiled again
+        let moved = AppInfo {
+            size: AppSize::new(500, 400),
+            ..app(1, "code.exe", 100, 100)
+        };
+        handler.update_apps(moved, WinEvent::SystemMovesizeend);
+        handler.toggle_float();
+
+        handler.toggle_float();
+        assert_eq!(
+            rect_of(&backend, 1),
+            Some((AppPosition::new(100, 100), AppSize::new(500, 400)))
+        );
+    }
+
+    #[test]
+    fn toggle_float_keeps_topmost_it_did_not_set() {
+        let (backend, mut handler) = setup();
+        handler.toggle_top_most();
+        handler.toggle_float();
+        handler.toggle_float();
+        assert!(!handler.floating_apps.contains(&1));
+        assert!(handler.top_most_apps.contains(&1));
+        assert_eq!(
+            backend
+                .calls()
+                .iter()
+                .filter(|c| **c == BackendCall::ToggleTopMost(1))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn rule_windows_become_hidden_scratchpads() {
+        let (backend, mut handler) = setup_scratchpad();
+        // off every workspace and out of sight
+        assert_eq!(workspace_of(&handler, 3), None);
+        assert_eq!(
+            backend.window(3).map(|w| w.position.y),
+            Some(layout::PARKED_Y)
+        );
+        // a second window of the same exe is a normal one
+        backend.add_window(4, AppPosition::new(100, 100), AppSize::new(800, 600), 0);
+        handler.update_apps(app(4, "wezterm.exe", 100, 100), WinEvent::ObjectShow);
+        assert_eq!(workspace_of(&handler, 4), Some(0));
+    }
+
+    #[test]
+    fn toggling_shows_a_scratchpad_and_hides_it_again() {
+        let (backend, mut handler) = setup_scratchpad();
+        backend.clear_calls();
+        handler.toggle_scratchpad("term").unwrap();
+        assert!(backend.calls().contains(&shown_scratchpad(&handler, 3)));
+        assert!(handler.top_most_apps.contains(&3));
+        assert_eq!(handler.current_active_app, Some(3));
+
+        handler.toggle_scratchpad("term").unwrap();
+        assert_eq!(
+            backend.window(3).map(|w| w.position.y),
+            Some(layout::PARKED_Y)
+        );
+        assert_ne!(handler.current_active_app, Some(3));
+    }
+
+    #[test]
+    fn a_closed_scratchpad_is_launched_and_shown_again() {
+        let (backend, mut handler) = setup_scratchpad();
+        handler.delete_app(&app(3, "wezterm.exe", 100, 100));
+        backend.clear_calls();
+        handler.toggle_scratchpad("term").unwrap();
+        assert_eq!(
+            backend.calls(),
+            vec![BackendCall::Launch("C:\\wezterm.exe".into())]
+        );
+        backend.add_window(5, AppPosition::new(100, 100), AppSize::new(800, 600), 0);
+        handler.update_apps(app(5, "wezterm.exe", 100, 100), WinEvent::ObjectCreate);
+        assert!(backend.calls().contains(&shown_scratchpad(&handler, 5)));
+        assert_eq!(workspace_of(&handler, 5), None);
+    }
+
+    #[test]
+    fn a_scratchpad_without_window_or_command_is_an_error() {
+        let (_, mut handler) = setup_scratchpad();
+        assert!(handler.toggle_scratchpad("notes").is_err());
+    }
+
+    #[test]
+    fn marking_takes_the_active_app_off_its_workspace() {
+        let (_, mut handler) = setup_scratchpad();
+        handler.current_active_app = Some(1);
+        handler.mark_scratchpad("notes").unwrap();
+        assert_eq!(workspace_of(&handler, 1), None);
+        // it is one already
+        handler.current_active_app = Some(1);
+        assert!(handler.mark_scratchpad("notes").is_err());
+    }
+
+    #[test]
+    fn reorder_app_pos_in_workspace_parks_inactive() {
+        let (backend, handler) = setup();
+        handler.reorder_app_pos_in_workspace();
+
+        let calls = backend.calls();
+        assert!(calls.contains(&BackendCall::SetPosition {
+            hwnd: 1,
+            x: 0,
+            y: 100
+        }));
+        assert!(calls.contains(&BackendCall::SetPosition {
+            hwnd: 2,
+            x: 0,
+            y: layout::PARKED_BELOW_Y
+        }));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::overlay::{
+    app_info::AppPosition, backend::WindowBackend, layout::LayoutRect, manager::STATUSBAR_HEIGHT,
+    monitor_info::StatusbarMonitorInfo, workspaces::Hwnd,
+};
+
+//==============================================================================//
+// tag         : RESCUE
+// description : windows parked off screen by a manager that is gone
+//==============================================================================//
+/// where a window at `rect` has to go to be seen again, `None` when some
+/// monitor already shows it. it lands on `monitor`, or the one it is above or
+/// below, at `y` or just under the statusbar
+pub fn on_screen_position(
+    rect: LayoutRect,
+    monitors: &[StatusbarMonitorInfo],
+    monitor: Option<usize>,
+    y: Option<i32>,
+) -> Option<AppPosition> {
+    if monitors
+        .iter()
+        .any(|m| LayoutRect::from(m).intersects(&rect))
+    {
+        return None;
+    }
+    let center = rect.x + rect.width / 2;
+    let m = monitor
+        .and_then(|i| monitors.get(i))
+        .or_else(|| {
+            monitors
+                .iter()
+                .find(|m| (m.x..m.x + m.width).contains(&center))
+        })
+        .or(monitors.first())?;
+    let max_x = m.x + (m.width - rect.width).max(0);
+    Some(AppPosition::new(
+        rect.x.clamp(m.x, max_x),
+        y.unwrap_or(m.y + STATUSBAR_HEIGHT as i32),
+    ))
+}
+
+/// every visible, not minimized top level window no monitor shows is moved
+/// back, for `tsck-window rescue` after a crash
+pub fn rescue_windows(backend: &dyn WindowBackend) -> Vec<Hwnd> {
+    let monitors = backend.get_monitors();
+    backend
+        .list_windows()
+        .into_iter()
+        .filter(|hwnd| !backend.is_minimized(*hwnd))
+        .filter_map(|hwnd| {
+            let (size, position) = backend.get_rect(hwnd);
+            let rect = LayoutRect::new(position.x, position.y, size.width, size.height);
+            let to = on_screen_position(rect, &monitors, None, None)?;
+            backend.set_position(hwnd, to.x, to.y);
+            Some(hwnd)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::{
+        app_info::AppSize,
+        backend::{BackendCall, FakeBackend},
+        layout::{PARKED_BELOW_Y, PARKED_Y},
+    };
+
+    fn monitor(index: usize, x: i32) -> StatusbarMonitorInfo {
+        StatusbarMonitorInfo {
+            handle: index as isize,
+            index,
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            is_primary: index == 0,
+        }
+    }
+
+    // an 800x600 window at `x`, `y` with two monitors side by side
+    fn position(x: i32, y: i32, on: Option<usize>, parked: Option<i32>) -> Option<AppPosition> {
+        let monitors = [monitor(0, 0), monitor(1, 1920)];
+        on_screen_position(LayoutRect::new(x, y, 800, 600), &monitors, on, parked)
+    }
+
+    #[test]
+    fn on_screen_windows_stay() {
+        assert_eq!(position(100, 100, None, None), None);
+    }
+
+    #[test]
+    fn windows_half_on_a_monitor_stay() {
+        assert_eq!(position(1500, 100, None, None), None);
+    }
+
+    #[test]
+    fn parked_above_goes_back_to_its_y() {
+        assert_eq!(
+            position(100, PARKED_Y, Some(0), Some(240)),
+            Some(AppPosition::new(100, 240))
+        );
+    }
+
+    #[test]
+    fn parked_below_goes_under_the_statusbar_of_its_monitor() {
+        assert_eq!(
+            position(2000, PARKED_BELOW_Y, None, None),
+            Some(AppPosition::new(2000, STATUSBAR_HEIGHT as i32))
+        );
+    }
+
+    #[test]
+    fn far_off_windows_go_onto_the_closest_edge() {
+        assert_eq!(
+            position(9000, PARKED_Y, Some(1), Some(50)),
+            Some(AppPosition::new(1920 + 1920 - 800, 50))
+        );
+    }
+
+    #[test]
+    fn rescue_skips_minimized_windows() {
+        let backend = FakeBackend::new(vec![monitor(0, 0)]);
+        let size = AppSize::new(800, 600);
+        backend.add_window(1, AppPosition::new(0, 100), size.clone(), 0);
+        backend.add_window(2, AppPosition::new(300, PARKED_Y), size.clone(), 0);
+        backend.add_window(3, AppPosition::new(-32000, -32000), size, 0);
+        backend.set_minimized(3, true);
+
+        assert_eq!(rescue_windows(&backend), vec![2]);
+        assert_eq!(
+            backend.calls(),
+            vec![BackendCall::SetPosition {
+                hwnd: 2,
+                x: 300,
+                y: STATUSBAR_HEIGHT as i32
+            }]
+        );
+    }
+}
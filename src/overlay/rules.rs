@@ -0,0 +1,380 @@
+use regex::Regex;
+
+use crate::overlay::{
+    app_info::AppInfo,
+    config::{NtekConfig, WindowRule},
+};
+
+/// `(x, y, width, height)` fractions of the usable area, same as `workspace_grid`
+pub type GridCell = (f32, f32, f32, f32);
+
+//==============================================================================//
+// tag         : PATTERN
+// description : `/regex/`, a glob with `*` or `?`, anything else is exact
+//==============================================================================//
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Exact(String),
+    Glob(String),
+    Regex(Regex),
+}
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, regex::Error> {
+        if let Some(re) = pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            return Ok(Pattern::Regex(Regex::new(re)?));
+        }
+        if pattern.contains(['*', '?']) {
+            return Ok(Pattern::Glob(pattern.to_string()));
+        }
+        Ok(Pattern::Exact(pattern.to_string()))
+    }
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Exact(exact) => exact == text,
+            Pattern::Glob(glob) => glob_match(glob, text),
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+/// `*` is any run of characters, `?` exactly one
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // last `*` seen and where in `text` it started matching
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// `#rrggbb` into the `0xrrggbb` the border overlay takes
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+//==============================================================================//
+// tag         : RULE
+// description : one `rules` entry with its patterns compiled
+//==============================================================================//
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    exe: Option<Pattern>,
+    class: Option<Pattern>,
+    title: Option<Pattern>,
+    ignore: Option<bool>,
+    float: Option<bool>,
+    top_most: Option<bool>,
+    workspace: Option<String>,
+    monitor: Option<usize>,
+    grid: Option<GridCell>,
+    border: Option<u32>,
+    scratchpad: Option<String>,
+}
+impl Rule {
+    pub fn from_config(rule: &WindowRule, grid: &[GridCell]) -> Result<Self, RuleError> {
+        let pattern = |field: &'static str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(Pattern::parse)
+                .transpose()
+                .map_err(|err| RuleError {
+                    field,
+                    message: err.to_string(),
+                })
+        };
+        let cell = match rule.grid {
+            Some(index) => Some(*grid.get(index).ok_or(RuleError {
+                field: "grid",
+                message: format!("workspace_grid has no cell {index}"),
+            })?),
+            None => None,
+        };
+        let border = match &rule.border {
+            Some(color) => Some(parse_color(color).ok_or(RuleError {
+                field: "border",
+                message: format!("\"{color}\" is not a #rrggbb colour"),
+            })?),
+            None => None,
+        };
+        Ok(Self {
+            exe: pattern("exe", &rule.exe)?,
+            class: pattern("class", &rule.class)?,
+            title: pattern("title", &rule.title)?,
+            ignore: rule.ignore,
+            float: rule.float,
+            top_most: rule.top_most,
+            workspace: rule.workspace.clone(),
+            monitor: rule.monitor,
+            grid: cell,
+            border,
+            scratchpad: rule.scratchpad.clone(),
+        })
+    }
+    /// every pattern that is set has to match, a rule without any matches nothing
+    pub fn matches(&self, app: &AppInfo) -> bool {
+        let checks = [
+            (&self.exe, app.exe.as_str()),
+            (&self.class, app.class.as_str()),
+            (&self.title, app.title.as_str()),
+        ];
+        checks.iter().any(|(pattern, _)| pattern.is_some())
+            && checks
+                .iter()
+                .all(|(pattern, text)| pattern.as_ref().is_none_or(|p| p.matches(text)))
+    }
+}
+
+//==============================================================================//
+// tag         : RULES
+// description : what every matching rule says about a window,
+//               later rules override earlier ones
+//==============================================================================//
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleActions {
+    pub ignore: bool,
+    pub float: bool,
+    pub top_most: bool,
+    pub workspace: Option<String>,
+    pub monitor: Option<usize>,
+    pub grid: Option<GridCell>,
+    pub border: Option<u32>,
+    pub scratchpad: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rules(Vec<Rule>);
+
+impl Rules {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self(rules)
+    }
+    /// broken rules are reported and skipped, `check-config` lists them properly
+    pub fn from_config(config: &NtekConfig) -> Self {
+        let grid = config.grid_cells();
+        let rules = config
+            .rules
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(i, rule)| match Rule::from_config(rule, &grid) {
+                Ok(rule) => Some(rule),
+                Err(err) => {
+                    eprintln!("Error in rules[{i}].{}: {}", err.field, err.message);
+                    None
+                }
+            })
+            .collect();
+        Self(rules)
+    }
+    pub fn resolve(&self, app: &AppInfo) -> RuleActions {
+        let mut actions = RuleActions::default();
+        for rule in self.0.iter().filter(|r| r.matches(app)) {
+            if let Some(ignore) = rule.ignore {
+                actions.ignore = ignore;
+            }
+            if let Some(float) = rule.float {
+                actions.float = float;
+            }
+            if let Some(top_most) = rule.top_most {
+                actions.top_most = top_most;
+            }
+            if rule.workspace.is_some() {
+                actions.workspace = rule.workspace.clone();
+            }
+            if rule.monitor.is_some() {
+                actions.monitor = rule.monitor;
+            }
+            if rule.grid.is_some() {
+                actions.grid = rule.grid;
+            }
+            if rule.border.is_some() {
+                actions.border = rule.border;
+            }
+            if rule.scratchpad.is_some() {
+                actions.scratchpad = rule.scratchpad.clone();
+            }
+        }
+        actions
+    }
+    pub fn is_ignored(&self, app: &AppInfo) -> bool {
+        self.resolve(app).ignore
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::app_info::{AppPosition, AppSize, Column, SizeRatio};
+
+    fn app(exe: &str, class: &str, title: &str) -> AppInfo {
+        AppInfo {
+            hwnd: 1,
+            exe: exe.to_string(),
+            exe_path: format!("C:\\{exe}"),
+            size: AppSize::new(800, 600),
+            position: AppPosition::new(0, 0),
+            title: title.to_string(),
+            class: class.to_string(),
+            column: Column::Left,
+            size_ratio: SizeRatio {
+                width: 1.0,
+                height: 1.0,
+            },
+        }
+    }
+
+    #[test]
+    fn exact_patterns() {
+        let exact = Pattern::parse("firefox.exe").unwrap();
+        assert!(exact.matches("firefox.exe"));
+        assert!(!exact.matches("firefox.exe2"));
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let glob = Pattern::parse("*fire?ox*").unwrap();
+        assert!(glob.matches("firefox.exe"));
+        assert!(glob.matches("Mozilla firebox"));
+        assert!(!glob.matches("fireox"));
+        assert!(Pattern::parse("a*b*c").unwrap().matches("aXbYbc"));
+    }
+
+    #[test]
+    fn regex_patterns() {
+        let re = Pattern::parse("/^Picture.in.[Pp]icture$/").unwrap();
+        assert!(re.matches("Picture-in-Picture"));
+        assert!(!re.matches("Picture-in-Picture 2"));
+    }
+
+    #[test]
+    fn broken_regex_is_an_error() {
+        assert!(Pattern::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn every_set_pattern_has_to_match() {
+        let rule = Rule::from_config(
+            &WindowRule {
+                exe: Some("firefox.exe".into()),
+                title: Some("/Picture/".into()),
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+        assert!(rule.matches(&app(
+            "firefox.exe",
+            "MozillaWindowClass",
+            "Picture-in-Picture"
+        )));
+        assert!(!rule.matches(&app("firefox.exe", "MozillaWindowClass", "Mozilla Firefox")));
+        assert!(!rule.matches(&app("chrome.exe", "Chrome", "Picture-in-Picture")));
+
+        let empty = Rule::from_config(&WindowRule::default(), &[]).unwrap();
+        assert!(!empty.matches(&app("firefox.exe", "", "")));
+    }
+
+    #[test]
+    fn later_rules_override() {
+        let grid = [(0.0, 0.0, 1.0, 1.0), (0.5, 0.0, 0.5, 1.0)];
+        let rules = Rules::new(
+            [
+                WindowRule {
+                    class: Some("Chrome*".into()),
+                    workspace: Some("Browsing".into()),
+                    float: Some(true),
+                    grid: Some(1),
+                    ..Default::default()
+                },
+                WindowRule {
+                    exe: Some("chrome.exe".into()),
+                    float: Some(false),
+                    border: Some("#8aadf4".into()),
+                    ..Default::default()
+                },
+            ]
+            .iter()
+            .map(|r| Rule::from_config(r, &grid).unwrap())
+            .collect(),
+        );
+        assert_eq!(
+            rules.resolve(&app("chrome.exe", "Chrome_WidgetWin_1", "New Tab")),
+            RuleActions {
+                float: false,
+                workspace: Some("Browsing".into()),
+                grid: Some((0.5, 0.0, 0.5, 1.0)),
+                border: Some(0x8aadf4),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rules.resolve(&app("code.exe", "Chrome_WidgetWin_1", "main.rs")),
+            RuleActions {
+                float: true,
+                workspace: Some("Browsing".into()),
+                grid: Some((0.5, 0.0, 0.5, 1.0)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rules.resolve(&app("notepad.exe", "Notepad", "")),
+            RuleActions::default()
+        );
+    }
+
+    #[test]
+    fn broken_rules_say_where() {
+        let err = |r: WindowRule| Rule::from_config(&r, &[]).unwrap_err().field;
+        assert_eq!(
+            err(WindowRule {
+                title: Some("/[/".into()),
+                ..Default::default()
+            }),
+            "title"
+        );
+        assert_eq!(
+            err(WindowRule {
+                grid: Some(0),
+                ..Default::default()
+            }),
+            "grid"
+        );
+        assert_eq!(
+            err(WindowRule {
+                border: Some("red".into()),
+                ..Default::default()
+            }),
+            "border"
+        );
+    }
+}
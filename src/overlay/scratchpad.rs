@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use crate::overlay::{config::NtekConfig, layout::LayoutRect, workspaces::Hwnd};
+
+// share of the monitor a scratchpad takes without `width` / `height`
+pub const SCRATCHPAD_SIZE: f32 = 0.6;
+
+//==============================================================================//
+// tag         : SCRATCHPAD
+// description : windows kept off every workspace, shown and sent away by name.
+//               hidden ones sit off screen like the windows of a workspace
+//               that isn't showing
+//==============================================================================//
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scratchpad {
+    pub hwnd: Option<Hwnd>,
+    // monitor it is showing on, `None` while hidden
+    pub shown_on: Option<usize>,
+    pub launch: Option<String>,
+    // exe of the last window it had, launched again when `launch` isn't set
+    pub exe_path: Option<String>,
+    pub width: f32,
+    pub height: f32,
+}
+impl Default for Scratchpad {
+    fn default() -> Self {
+        Self {
+            hwnd: None,
+            shown_on: None,
+            launch: None,
+            exe_path: None,
+            width: SCRATCHPAD_SIZE,
+            height: SCRATCHPAD_SIZE,
+        }
+    }
+}
+impl Scratchpad {
+    /// what to run when there is no window
+    pub fn command(&self) -> Option<&str> {
+        self.launch.as_deref().or(self.exe_path.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scratchpads {
+    pads: BTreeMap<String, Scratchpad>,
+    // launched and shown as soon as its window turns up
+    pending: Option<String>,
+}
+impl Scratchpads {
+    /// launch commands and sizes from `scratchpads`, windows already taken stay
+    pub fn apply_config(&mut self, config: &NtekConfig) {
+        for pad in self.pads.values_mut() {
+            *pad = Scratchpad {
+                hwnd: pad.hwnd,
+                shown_on: pad.shown_on,
+                exe_path: pad.exe_path.take(),
+                ..Default::default()
+            };
+        }
+        for entry in config.scratchpads.iter().flatten() {
+            let pad = self.pads.entry(entry.name.clone()).or_default();
+            pad.launch = entry.launch.clone();
+            pad.width = entry.width.unwrap_or(SCRATCHPAD_SIZE).clamp(0.1, 1.0);
+            pad.height = entry.height.unwrap_or(SCRATCHPAD_SIZE).clamp(0.1, 1.0);
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Scratchpad> {
+        self.pads.get(name)
+    }
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Scratchpad> {
+        self.pads.get_mut(name)
+    }
+    /// name of the scratchpad `hwnd` is
+    pub fn name_of(&self, hwnd: Hwnd) -> Option<&str> {
+        self.pads
+            .iter()
+            .find(|(_, pad)| pad.hwnd == Some(hwnd))
+            .map(|(name, _)| name.as_str())
+    }
+    /// `hwnd` becomes scratchpad `name`, `None` when that already has a window.
+    /// `Some(true)` when it was launched and should show right away
+    pub fn take(&mut self, name: &str, hwnd: Hwnd, exe_path: &str) -> Option<bool> {
+        let pad = self.pads.entry(name.to_string()).or_default();
+        if pad.hwnd.is_some() {
+            return None;
+        }
+        pad.hwnd = Some(hwnd);
+        pad.shown_on = None;
+        pad.exe_path = Some(exe_path.to_string());
+        let pending = self.pending.as_deref() == Some(name);
+        if pending {
+            self.pending = None;
+        }
+        Some(pending)
+    }
+    /// the window of a scratchpad closed, the next toggle launches it again
+    pub fn forget(&mut self, hwnd: Hwnd) {
+        for pad in self.pads.values_mut().filter(|pad| pad.hwnd == Some(hwnd)) {
+            pad.hwnd = None;
+            pad.shown_on = None;
+        }
+    }
+    pub fn set_pending(&mut self, name: &str) {
+        self.pending = Some(name.to_string());
+    }
+    /// windows of the scratchpads that are hidden
+    pub fn hidden(&self) -> Vec<Hwnd> {
+        self.pads
+            .values()
+            .filter(|pad| pad.shown_on.is_none())
+            .filter_map(|pad| pad.hwnd)
+            .collect()
+    }
+}
+
+/// `width` and `height` of `area` in its middle
+pub fn centered_rect(area: LayoutRect, width: f32, height: f32) -> LayoutRect {
+    let w = (area.width as f32 * width) as i32;
+    let h = (area.height as f32 * height) as i32;
+    LayoutRect::new(
+        area.x + (area.width - w) / 2,
+        area.y + (area.height - h) / 2,
+        w,
+        h,
+    )
+}
+
+/// program and the rest of `command`, a program with spaces has to be quoted
+pub fn split_command(command: &str) -> (&str, &str) {
+    let command = command.trim();
+    if let Some((program, rest)) = command
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.split_once('"'))
+    {
+        return (program, rest.trim());
+    }
+    match command.split_once(char::is_whitespace) {
+        Some((program, rest)) => (program, rest.trim()),
+        None => (command, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_scratchpad_has_one_window_at_a_time() {
+        let mut pads = Scratchpads::default();
+        pads.set_pending("term");
+        assert_eq!(pads.take("term", 7, "C:\\wezterm-gui.exe"), Some(true));
+        assert_eq!(pads.take("term", 8, "C:\\wezterm-gui.exe"), None);
+        assert_eq!(pads.name_of(7), Some("term"));
+        assert_eq!(pads.hidden(), [7]);
+
+        pads.forget(7);
+        assert_eq!(pads.name_of(7), None);
+        let term = pads.get("term").unwrap();
+        assert_eq!(term.command(), Some("C:\\wezterm-gui.exe"));
+        // nobody waits for it any more
+        assert_eq!(pads.take("term", 8, "C:\\wezterm-gui.exe"), Some(false));
+    }
+
+    #[test]
+    fn scratchpads_sit_in_the_middle() {
+        let area = LayoutRect::new(1920, 40, 1920, 1040);
+        assert_eq!(
+            centered_rect(area, 0.5, 0.5),
+            LayoutRect::new(1920 + 480, 40 + 260, 960, 520)
+        );
+    }
+
+    #[test]
+    fn commands_split_after_the_program() {
+        assert_eq!(split_command("wezterm-gui.exe"), ("wezterm-gui.exe", ""));
+        assert_eq!(
+            split_command(" wezterm-gui.exe start --class scratch "),
+            ("wezterm-gui.exe", "start --class scratch")
+        );
+        assert_eq!(
+            split_command("\"C:\\Program Files\\Obsidian\\Obsidian.exe\" --new"),
+            ("C:\\Program Files\\Obsidian\\Obsidian.exe", "--new")
+        );
+    }
+}
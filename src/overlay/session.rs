@@ -0,0 +1,290 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::overlay::{
+    app_info::AppInfo,
+    config::LayoutKind,
+    config_file,
+    layout::LayoutRect,
+    workspaces::{Hwnd, Workspace},
+};
+
+/// environment variable pointing at the session file
+pub const SESSION_ENV: &str = "TSCK_WINDOW_SESSION";
+const SESSION_NAME: &str = "session.json";
+
+/// `$TSCK_WINDOW_SESSION`, else `session.json` next to the per-user config
+pub fn session_path() -> Option<PathBuf> {
+    match std::env::var_os(SESSION_ENV).filter(|v| !v.is_empty()) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(config_file::user_config_path()?.with_file_name(SESSION_NAME)),
+    }
+}
+
+//==============================================================================//
+// tag         : SESSION
+// description : which window was on which workspace, kept across restarts.
+//               hwnds don't survive a reboot, windows are matched again by
+//               exe, class and title
+//==============================================================================//
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Session {
+    pub workspaces: Vec<SavedWorkspace>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedWorkspace {
+    pub name: String,
+    pub layout: LayoutKind,
+    pub master_count: usize,
+    pub master_ratio: f32,
+    pub windows: Vec<SavedWindow>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedWindow {
+    pub hwnd: Hwnd,
+    pub exe: String,
+    pub class: String,
+    pub title: String,
+    pub monitor: usize,
+    pub floating: bool,
+    // only for windows nothing else puts in place, floating ones or on a floating workspace
+    #[serde(default)]
+    pub rect: Option<LayoutRect>,
+}
+impl SavedWindow {
+    /// exe and class have to be the same, the same title and hwnd make it a
+    /// better match
+    fn score(&self, app: &AppInfo) -> Option<u32> {
+        if self.exe != app.exe || self.class != app.class {
+            return None;
+        }
+        let mut score = 1;
+        if self.title == app.title {
+            score += 2;
+        }
+        if self.hwnd == app.hwnd {
+            score += 4;
+        }
+        Some(score)
+    }
+}
+
+/// a saved window handed to a live one, with the workspace it was on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claimed {
+    pub workspace: String,
+    pub window: SavedWindow,
+}
+
+impl Session {
+    /// a missing file is an empty session
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+    /// written next to `path` first, a crash mid write leaves the old file alone
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(tmp, path)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.workspaces.iter().all(|ws| ws.windows.is_empty())
+    }
+    /// the same windows in the same places, titles change too often to count
+    /// and only help `claim` pick between windows of a kind
+    pub fn same_layout(&self, other: &Session) -> bool {
+        let untitled = |session: &Session| {
+            let mut session = session.clone();
+            for window in session
+                .workspaces
+                .iter_mut()
+                .flat_map(|ws| ws.windows.iter_mut())
+            {
+                window.title.clear();
+            }
+            session
+        };
+        untitled(self) == untitled(other)
+    }
+    /// the best saved entry for `app`, taken out so no other window gets it
+    pub fn claim(&mut self, app: &AppInfo) -> Option<Claimed> {
+        let (ws, i, _) = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .flat_map(|(ws, saved)| {
+                saved
+                    .windows
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(i, w)| Some((ws, i, w.score(app)?)))
+            })
+            .max_by_key(|(_, _, score)| *score)?;
+        let saved = &mut self.workspaces[ws];
+        Some(Claimed {
+            workspace: saved.name.clone(),
+            window: saved.windows.remove(i),
+        })
+    }
+    /// layout and master stack of every saved workspace that still exists
+    pub fn apply_layouts(&self, workspaces: &mut [Workspace]) {
+        for saved in self.workspaces.iter() {
+            if let Some(ws) = workspaces.iter_mut().find(|ws| ws.text == saved.name) {
+                ws.layout = saved.layout;
+                ws.master_stack.master_count = saved.master_count;
+                ws.master_stack.master_ratio = saved.master_ratio;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay::app_info::{AppPosition, AppSize, Column, SizeRatio};
+
+    fn app(hwnd: Hwnd, exe: &str, class: &str, title: &str) -> AppInfo {
+        AppInfo {
+            hwnd,
+            exe: exe.to_string(),
+            exe_path: format!("C:\\{exe}"),
+            size: AppSize::new(800, 600),
+            position: AppPosition::new(0, 0),
+            title: title.to_string(),
+            class: class.to_string(),
+            column: Column::Left,
+            size_ratio: SizeRatio {
+                width: 1.0,
+                height: 1.0,
+            },
+        }
+    }
+    fn saved(hwnd: Hwnd, exe: &str, class: &str, title: &str) -> SavedWindow {
+        SavedWindow {
+            hwnd,
+            exe: exe.to_string(),
+            class: class.to_string(),
+            title: title.to_string(),
+            monitor: 0,
+            floating: false,
+            rect: None,
+        }
+    }
+    fn session() -> Session {
+        let workspace = |name: &str, windows| SavedWorkspace {
+            name: name.to_string(),
+            layout: LayoutKind::Bsp,
+            master_count: 2,
+            master_ratio: 0.6,
+            windows,
+        };
+        Session {
+            workspaces: vec![
+                workspace(
+                    "Coding",
+                    vec![saved(
+                        10,
+                        "wezterm-gui.exe",
+                        "org.wezfurlong.wezterm",
+                        "vim",
+                    )],
+                ),
+                workspace(
+                    "Browsing",
+                    vec![
+                        saved(20, "firefox.exe", "MozillaWindowClass", "Mozilla Firefox"),
+                        saved(21, "firefox.exe", "MozillaWindowClass", "GitHub"),
+                    ],
+                ),
+            ],
+        }
+    }
+
+    fn claimed_hwnd(session: &mut Session, app: AppInfo) -> Option<Hwnd> {
+        session.claim(&app).map(|c| c.window.hwnd)
+    }
+
+    #[test]
+    fn the_same_hwnd_wins() {
+        // the manager was only restarted, the title changed meanwhile
+        let app = app(21, "firefox.exe", "MozillaWindowClass", "x");
+        assert_eq!(claimed_hwnd(&mut session(), app), Some(21));
+    }
+
+    #[test]
+    fn the_title_picks_between_two_of_a_kind() {
+        // after a reboot the hwnds are new
+        let app = app(7, "firefox.exe", "MozillaWindowClass", "GitHub");
+        assert_eq!(claimed_hwnd(&mut session(), app), Some(21));
+    }
+
+    #[test]
+    fn every_entry_goes_to_one_window_only() {
+        let mut session = session();
+        let github = |hwnd| app(hwnd, "firefox.exe", "MozillaWindowClass", "GitHub");
+        assert_eq!(claimed_hwnd(&mut session, github(7)), Some(21));
+        assert_eq!(claimed_hwnd(&mut session, github(8)), Some(20));
+        assert_eq!(claimed_hwnd(&mut session, github(9)), None);
+    }
+
+    #[test]
+    fn another_class_is_another_window() {
+        let app = app(10, "wezterm-gui.exe", "ConsoleWindowClass", "vim");
+        assert_eq!(claimed_hwnd(&mut session(), app), None);
+    }
+
+    #[test]
+    fn a_claim_says_which_workspace_the_window_was_on() {
+        let mut session = session();
+        assert_eq!(
+            session.claim(&app(3, "wezterm-gui.exe", "org.wezfurlong.wezterm", "zsh")),
+            Some(Claimed {
+                workspace: "Coding".to_string(),
+                window: saved(10, "wezterm-gui.exe", "org.wezfurlong.wezterm", "vim"),
+            })
+        );
+        assert!(session.workspaces[0].windows.is_empty());
+    }
+
+    #[test]
+    fn saved_layouts_go_to_workspaces_of_the_same_name() {
+        let mut workspaces = vec![
+            Workspace::new("Browsing", vec![]),
+            Workspace::new("Music", vec![]),
+        ];
+        session().apply_layouts(&mut workspaces);
+        assert_eq!(workspaces[0].layout, LayoutKind::Bsp);
+        assert_eq!(workspaces[0].master_stack.master_count, 2);
+        assert_eq!(workspaces[1].layout, LayoutKind::default());
+    }
+
+    #[test]
+    fn titles_dont_change_the_layout() {
+        let mut renamed = session();
+        renamed.workspaces[1].windows[0].title = "New Tab".to_string();
+        assert!(session().same_layout(&renamed));
+        renamed.workspaces[1].windows[0].floating = true;
+        assert!(!session().same_layout(&renamed));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("tsck-session-{}.json", std::process::id()));
+        let session = session();
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Session::default());
+    }
+}
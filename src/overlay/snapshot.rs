@@ -0,0 +1,85 @@
+use std::fmt;
+
+use crate::overlay::{
+    app_info::AppInfo,
+    monitor_info::StatusbarMonitorInfo,
+    workspaces::{Hwnd, WorkspaceInfo},
+};
+
+//==============================================================================//
+// tag         : SNAPSHOT
+// description : everything the manager knows in one go, for bug reports
+//==============================================================================//
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StateSnapshot {
+    pub monitors: Vec<StatusbarMonitorInfo>,
+    // workspace showing on each monitor, same order as `monitors`
+    pub active_workspace_per_monitor: Vec<usize>,
+    pub workspaces: Vec<WorkspaceInfo>,
+    pub apps: Vec<AppInfo>,
+    pub focused: Option<Hwnd>,
+    pub top_most: Vec<Hwnd>,
+    pub floating: Vec<Hwnd>,
+}
+
+/// the console `list`, one line per monitor, workspace and window
+impl fmt::Display for StateSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (m, active) in self.monitors.iter().zip(&self.active_workspace_per_monitor) {
+            let primary = if m.is_primary { " primary" } else { "" };
+            writeln!(
+                f,
+                "monitor {} {}x{}+{}+{}{primary}, showing workspace {active}",
+                m.index, m.width, m.height, m.x, m.y
+            )?;
+        }
+        for ws in self.workspaces.iter() {
+            writeln!(f, "workspace {} {} {:?}", ws.index, ws.name, ws.layout)?;
+            for item in ws.windows.iter() {
+                let parked = match item.parked_position {
+                    Some(y) => format!("parked {y}"),
+                    None => "-".to_string(),
+                };
+                write!(
+                    f,
+                    "  {:<10} {:<24} monitor {} {parked}",
+                    item.hwnd, item.app_name, item.monitor
+                )?;
+                if let Some(app) = self.apps.iter().find(|a| a.hwnd == item.hwnd) {
+                    write!(f, " at {} {}", app.position, app.size)?;
+                }
+                for (set, flag) in [
+                    (Some(item.hwnd) == self.focused, "focused"),
+                    (self.floating.contains(&item.hwnd), "floating"),
+                    (self.top_most.contains(&item.hwnd), "top_most"),
+                ] {
+                    if set {
+                        write!(f, " {flag}")?;
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+        let unassigned: Vec<&AppInfo> = self
+            .apps
+            .iter()
+            .filter(|a| {
+                !self
+                    .workspaces
+                    .iter()
+                    .any(|ws| ws.windows.iter().any(|h| h.hwnd == a.hwnd))
+            })
+            .collect();
+        if !unassigned.is_empty() {
+            writeln!(f, "not on a workspace")?;
+            for app in unassigned {
+                writeln!(
+                    f,
+                    "  {:<10} {:<24} at {} {}",
+                    app.hwnd, app.exe, app.position, app.size
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
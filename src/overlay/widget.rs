@@ -10,7 +10,7 @@ use crate::overlay::{
     color,
     manager::{STATUSBAR_HEIGHT, Shared, WM_UPDATE_STATUSBAR},
     statusbar::{SlotText, StatusBar, StatusBarFont, Visibility},
-    workspaces::{Hwnd, Workspace},
+    workspaces::{Hwnd, HwndItem, Workspace},
 };
 
 pub enum WorkspaceIndicatorPosition {
@@ -29,9 +29,12 @@ pub struct WidgetSlots {
     pub center: BTreeMap<String, Vec<SlotText>>,
     pub right: BTreeMap<String, Vec<SlotText>>,
     pub workspace_indicator: WorkspaceIndicatorPosition,
-    pub hwnd: Option<isize>,
+    // one statusbar per monitor, in monitor order
+    pub statusbars: Shared<Vec<isize>>,
     pub workspaces: Vec<Workspace>,
     pub active_workspace_per_monitor: Vec<usize>,
+    // what each monitor showed before, most recent first
+    pub workspace_history: BTreeMap<usize, Vec<usize>>,
 }
 
 impl Default for WidgetSlots {
@@ -41,9 +44,10 @@ impl Default for WidgetSlots {
             center: BTreeMap::new(),
             right: BTreeMap::new(),
             workspace_indicator: WorkspaceIndicatorPosition::Center,
-            hwnd: None,
+            statusbars: Arc::new(Mutex::new(vec![])),
             workspaces: vec![],
-            active_workspace_per_monitor: vec![0; 2],
+            active_workspace_per_monitor: vec![],
+            workspace_history: BTreeMap::new(),
         }
     }
 }
@@ -58,8 +62,62 @@ impl WidgetSlots {
             .copied()
             .unwrap_or(0)
     }
-    pub fn set_hwnd(&mut self, hwnd: Option<isize>) {
-        self.hwnd = hwnd;
+    /// indices of the workspaces `monitor` can show, all of them unless
+    /// monitors have their own
+    pub fn workspaces_on(&self, monitor: usize) -> Vec<usize> {
+        self.workspaces
+            .iter()
+            .enumerate()
+            .filter(|(_, ws)| ws.belongs_to(monitor))
+            .map(|(i, _)| i)
+            .collect()
+    }
+    /// one active workspace per monitor, monitors that came or went get or lose theirs
+    pub fn set_monitor_count(&mut self, count: usize) {
+        self.active_workspace_per_monitor.resize(count, 0);
+        self.workspace_history.retain(|monitor, _| *monitor < count);
+        self.fit_active();
+    }
+    /// every monitor shows a workspace it can show
+    fn fit_active(&mut self) {
+        for monitor in 0..self.active_workspace_per_monitor.len() {
+            let active = self.active_workspace_per_monitor[monitor];
+            if self
+                .workspaces
+                .get(active)
+                .is_none_or(|ws| !ws.belongs_to(monitor))
+            {
+                self.active_workspace_per_monitor[monitor] =
+                    self.workspaces_on(monitor).first().copied().unwrap_or(0);
+            }
+        }
+    }
+    /// shows `index` on `monitor`, the workspace it replaces goes to the front
+    /// of that monitor's history. `None` when it is another monitor's workspace
+    pub fn set_active_workspace(&mut self, monitor: usize, index: usize) -> Option<()> {
+        if !self.workspaces.get(index)?.belongs_to(monitor) {
+            return None;
+        }
+        let active = self.active_workspace_per_monitor.get_mut(monitor)?;
+        let previous = std::mem::replace(active, index);
+        if previous != index {
+            let history = self.workspace_history.entry(monitor).or_default();
+            history.retain(|i| *i != previous && *i != index);
+            history.insert(0, previous);
+        }
+        Some(())
+    }
+    /// the workspace `monitor` showed before the current one
+    pub fn previous_workspace(&self, monitor: usize) -> Option<usize> {
+        let active = self.get_active_workspace_for_monitor(monitor);
+        self.workspace_history
+            .get(&monitor)?
+            .iter()
+            .copied()
+            .find(|i| *i != active && *i < self.workspaces.len())
+    }
+    pub fn set_statusbars(&mut self, statusbars: Shared<Vec<isize>>) {
+        self.statusbars = statusbars;
     }
     pub fn set_slot(&mut self, grid: SlotGrid, key: &str, slots: Vec<SlotText>) {
         {
@@ -78,6 +136,182 @@ impl WidgetSlots {
 
         self.refresh_statusbar();
     }
+    pub fn remove_slot(&mut self, grid: SlotGrid, key: &str) {
+        let removed = match grid {
+            SlotGrid::Left => self.left.remove(key),
+            SlotGrid::Center => self.center.remove(key),
+            SlotGrid::Right => self.right.remove(key),
+        };
+        if removed.is_some() {
+            self.refresh_statusbar();
+        }
+    }
+    /// rebuild the workspace list from `names`, each with the monitor it belongs
+    /// to. workspaces are matched by monitor and name, then by name, then by
+    /// position (a rename), anything else is made by `new_workspace`. windows of
+    /// workspaces that are gone move to the last one their monitor can show,
+    /// nothing gets lost.
+    pub fn sync_workspaces(
+        &mut self,
+        names: &[(Option<usize>, String)],
+        new_workspace: impl Fn(&str) -> Workspace,
+    ) {
+        if names.is_empty() {
+            return;
+        }
+        let mut old: Vec<Option<Workspace>> = std::mem::take(&mut self.workspaces)
+            .into_iter()
+            .map(Some)
+            .collect();
+        // old index of every new slot
+        let mut origin: Vec<Option<usize>> = vec![None; names.len()];
+        let mut slots: Vec<Option<Workspace>> = vec![None; names.len()];
+        let same_monitor = |ws: &Workspace, monitor: &Option<usize>| ws.monitor == *monitor;
+        // the same name on another monitor is the list moving between modes
+        for any_monitor in [false, true] {
+            for (i, (monitor, name)) in names.iter().enumerate() {
+                if slots[i].is_some() {
+                    continue;
+                }
+                if let Some(j) = old.iter().position(|ws| {
+                    ws.as_ref().is_some_and(|ws| {
+                        ws.text == *name && (any_monitor || same_monitor(ws, monitor))
+                    })
+                }) {
+                    slots[i] = old[j].take().map(|ws| Workspace {
+                        monitor: *monitor,
+                        ..ws
+                    });
+                    origin[i] = Some(j);
+                }
+            }
+        }
+        for (i, (monitor, name)) in names.iter().enumerate() {
+            if slots[i].is_some() {
+                continue;
+            }
+            let renamed = old
+                .get_mut(i)
+                .filter(|ws| {
+                    ws.as_ref().is_some_and(|ws| {
+                        same_monitor(ws, monitor) && !names.iter().any(|(_, n)| *n == ws.text)
+                    })
+                })
+                .and_then(|ws| ws.take());
+            slots[i] = Some(match renamed {
+                Some(ws) => {
+                    origin[i] = Some(i);
+                    Workspace {
+                        text: name.clone(),
+                        ..ws
+                    }
+                }
+                None => Workspace {
+                    active: false,
+                    monitor: *monitor,
+                    ..new_workspace(name)
+                },
+            });
+        }
+        let mut workspaces: Vec<Workspace> = slots.into_iter().flatten().collect();
+        let last = workspaces.len() - 1;
+        let mut homeless: Vec<HwndItem> =
+            old.into_iter().flatten().flat_map(|ws| ws.hwnds).collect();
+        // a workspace that went to another monitor leaves the windows it had elsewhere
+        for ws in workspaces.iter_mut() {
+            let (stay, strays): (Vec<HwndItem>, Vec<HwndItem>) = std::mem::take(&mut ws.hwnds)
+                .into_iter()
+                .partition(|item| ws.belongs_to(item.monitor));
+            ws.hwnds = stay;
+            homeless.extend(strays);
+        }
+        for item in homeless {
+            let into = workspaces
+                .iter()
+                .rposition(|ws| ws.belongs_to(item.monitor))
+                .unwrap_or(last);
+            workspaces[into].hwnds.push(item);
+        }
+        self.workspaces = workspaces;
+        self.reindex(|i| origin.iter().position(|o| *o == Some(i)), last);
+        self.fit_active();
+    }
+    /// names pick workspaces in commands and rules, they have to be there and
+    /// unique among the ones `monitor` shows
+    pub fn check_workspace_name(&self, name: &str, monitor: usize) -> anyhow::Result<()> {
+        if name.is_empty() {
+            anyhow::bail!("a workspace needs a name");
+        }
+        if self
+            .workspaces
+            .iter()
+            .any(|ws| ws.text == name && ws.belongs_to(monitor))
+        {
+            anyhow::bail!("there already is a workspace `{name}`");
+        }
+        Ok(())
+    }
+    /// appended at the end, returns its index
+    pub fn add_workspace(&mut self, workspace: Workspace) -> usize {
+        self.workspaces.push(workspace);
+        self.workspaces.len() - 1
+    }
+    /// drops workspace `index`, its windows go to the one before it (after it
+    /// for the first one) on the same monitor and so do monitors showing it.
+    /// returns where that neighbour is now, `None` for the last workspace left
+    pub fn remove_workspace(&mut self, index: usize) -> Option<usize> {
+        let owner = self.workspaces.get(index)?.monitor;
+        let same_owner: Vec<usize> = (0..self.workspaces.len())
+            .filter(|i| *i != index && self.workspaces[*i].monitor == owner)
+            .collect();
+        let neighbour = same_owner
+            .iter()
+            .rev()
+            .find(|i| **i < index)
+            .or(same_owner.first())
+            .copied()?;
+        let removed = self.workspaces.remove(index);
+        let into = if neighbour > index {
+            neighbour - 1
+        } else {
+            neighbour
+        };
+        self.workspaces[into].hwnds.extend(removed.hwnds);
+        self.reindex(
+            |i| match i {
+                i if i == index => None,
+                i if i > index => Some(i - 1),
+                i => Some(i),
+            },
+            into,
+        );
+        Some(into)
+    }
+    pub fn swap_workspaces(&mut self, a: usize, b: usize) -> Option<()> {
+        if a.max(b) >= self.workspaces.len() {
+            return None;
+        }
+        self.workspaces.swap(a, b);
+        self.reindex(
+            |i| match i {
+                i if i == a => Some(b),
+                i if i == b => Some(a),
+                i => Some(i),
+            },
+            0,
+        );
+        Some(())
+    }
+    /// the workspace list changed, `moved` says where each old index is now,
+    /// monitors showing one that is gone show `fallback`
+    fn reindex(&mut self, moved: impl Fn(usize) -> Option<usize>, fallback: usize) {
+        for active in self.active_workspace_per_monitor.iter_mut() {
+            *active = moved(*active).unwrap_or(fallback);
+        }
+        for history in self.workspace_history.values_mut() {
+            *history = history.iter().filter_map(|i| moved(*i)).collect();
+        }
+    }
     fn update_statusbar(&self, target_hwnd: isize, statusbar: StatusBar) -> anyhow::Result<()> {
         let hwnd = HWND(target_hwnd as *mut std::ffi::c_void);
         unsafe {
@@ -103,9 +337,14 @@ impl WidgetSlots {
         workspaces
             .iter()
             .enumerate()
+            .filter(|(_, ws)| ws.belongs_to(monitor_index))
             .map(|(idx, ws)| {
                 let has_apps = ws.hwnds.iter().any(|h| h.monitor == monitor_index);
-                SlotText::new(format!("{} :{}", ws.text, ws.hwnds.len()))
+                let count = match ws.monocle_position(monitor_index) {
+                    Some((n, m)) if active == idx => format!("{n}/{m}"),
+                    _ => format!(":{}", ws.hwnds.len()),
+                };
+                SlotText::new(format!("{} {}", ws.text, count))
                     .fg(if has_apps {
                         if active == idx { color::BG } else { color::FG }
                     } else {
@@ -121,9 +360,20 @@ impl WidgetSlots {
             })
             .collect()
     }
+    /// every statusbar shows the workspaces of its own monitor
     pub fn refresh_statusbar(&mut self) {
-        let ws =
-            self.get_workspace_indicator(&self.workspaces, &self.active_workspace_per_monitor, 0);
+        let statusbars = self.statusbars.lock().clone();
+        for (monitor, hwnd) in statusbars.into_iter().enumerate() {
+            let statusbar = self.statusbar_for(monitor);
+            _ = self.update_statusbar(hwnd, statusbar);
+        }
+    }
+    fn statusbar_for(&self, monitor: usize) -> StatusBar {
+        let ws = self.get_workspace_indicator(
+            &self.workspaces,
+            &self.active_workspace_per_monitor,
+            monitor,
+        );
         let mut left = self.left.values().flatten().cloned().collect();
         let mut center = self.center.values().flatten().cloned().collect();
         let mut right = self.right.values().flatten().cloned().collect();
@@ -145,7 +395,7 @@ impl WidgetSlots {
             }
             WorkspaceIndicatorPosition::None => {}
         }
-        let statusbar = StatusBar {
+        StatusBar {
             left,
             center,
             right,
@@ -156,9 +406,243 @@ impl WidgetSlots {
                 family: "MartianMono NF".into(),
                 size: 10.0,
             },
-        };
-        if let Some(raw) = self.hwnd {
-            _ = self.update_statusbar(raw, statusbar);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(names: &[&str]) -> WidgetSlots {
+        let mut slots = WidgetSlots::default();
+        slots.workspaces = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Workspace::new(name, vec![HwndItem::new(i as Hwnd + 1, "app.exe", 0)]))
+            .collect();
+        slots.set_monitor_count(2);
+        slots
+    }
+    fn names(slots: &WidgetSlots) -> Vec<(String, Vec<Hwnd>)> {
+        slots
+            .workspaces
+            .iter()
+            .map(|ws| (ws.text.clone(), ws.hwnds.iter().map(|h| h.hwnd).collect()))
+            .collect()
+    }
+    fn strings(names: &[&str]) -> Vec<(Option<usize>, String)> {
+        names.iter().map(|s| (None, s.to_string())).collect()
+    }
+    fn indicator(slots: &WidgetSlots, monitor: usize) -> Vec<String> {
+        slots
+            .get_workspace_indicator(
+                &slots.workspaces,
+                &slots.active_workspace_per_monitor,
+                monitor,
+            )
+            .into_iter()
+            .map(|slot| slot.text)
+            .collect()
+    }
+    /// `Work` and `Files` on the first monitor, `Chat` on the second
+    fn owned_slots() -> WidgetSlots {
+        let mut slots = slots(&["Work", "Browsing"]);
+        slots.workspaces[1].hwnds[0].monitor = 1;
+        slots.active_workspace_per_monitor = vec![0, 1];
+        let owned = [(Some(0), "Work"), (Some(0), "Files"), (Some(1), "Chat")];
+        let owned: Vec<_> = owned.iter().map(|(m, n)| (*m, n.to_string())).collect();
+        slots.sync_workspaces(&owned, |name| Workspace::new(name, vec![]));
+        slots
+    }
+
+    #[test]
+    fn sync_workspaces_renames_and_adds() {
+        let mut slots = slots(&["Work", "Browsing"]);
+        slots.active_workspace_per_monitor = vec![1, 0];
+        slots.sync_workspaces(&strings(&["Code", "Browsing", "Files"]), |name| {
+            Workspace::new(name, vec![])
+        });
+        assert_eq!(
+            names(&slots),
+            vec![
+                ("Code".to_string(), vec![1]),
+                ("Browsing".to_string(), vec![2]),
+                ("Files".to_string(), vec![]),
+            ]
+        );
+        assert_eq!(slots.active_workspace_per_monitor, vec![1, 0]);
+        assert!(!slots.workspaces[2].active);
+    }
+
+    #[test]
+    fn sync_workspaces_follows_moved_names_and_keeps_windows() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        slots.active_workspace_per_monitor = vec![2, 1];
+        slots.sync_workspaces(&strings(&["Browsing", "Work"]), |name| {
+            Workspace::new(name, vec![])
+        });
+        assert_eq!(
+            names(&slots),
+            vec![
+                ("Browsing".to_string(), vec![2]),
+                ("Work".to_string(), vec![1, 3]),
+            ]
+        );
+        // `Files` is gone, its monitor shows where its windows went
+        assert_eq!(slots.active_workspace_per_monitor, vec![1, 0]);
+    }
+
+    #[test]
+    fn previous_workspace_goes_back_and_forth() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        assert_eq!(slots.previous_workspace(0), None);
+        slots.set_active_workspace(0, 2).unwrap();
+        slots.set_active_workspace(0, 1).unwrap();
+        assert_eq!(slots.previous_workspace(0), Some(2));
+        slots.set_active_workspace(0, 2).unwrap();
+        assert_eq!(slots.previous_workspace(0), Some(1));
+    }
+
+    #[test]
+    fn every_monitor_has_its_own_previous_workspace() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        slots.set_active_workspace(0, 2).unwrap();
+        assert_eq!(slots.previous_workspace(0), Some(0));
+        assert_eq!(slots.previous_workspace(1), None);
+        assert!(slots.set_active_workspace(5, 0).is_none());
+    }
+
+    #[test]
+    fn the_previous_workspace_follows_a_sync() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        slots.set_active_workspace(0, 1).unwrap();
+        slots.set_active_workspace(0, 2).unwrap();
+
+        slots.sync_workspaces(&strings(&["Files", "Browsing"]), |name| {
+            Workspace::new(name, vec![])
+        });
+        assert_eq!(slots.active_workspace_per_monitor[0], 0);
+        assert_eq!(slots.previous_workspace(0), Some(1));
+    }
+
+    #[test]
+    fn removing_moves_windows_and_reindexes() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        slots.set_active_workspace(0, 2).unwrap();
+        slots.set_active_workspace(1, 1).unwrap();
+
+        assert_eq!(slots.remove_workspace(1), Some(0));
+        assert_eq!(
+            names(&slots),
+            vec![
+                ("Work".to_string(), vec![1, 2]),
+                ("Files".to_string(), vec![3]),
+            ]
+        );
+        assert_eq!(slots.active_workspace_per_monitor, vec![1, 0]);
+        assert_eq!(slots.previous_workspace(0), Some(0));
+    }
+
+    #[test]
+    fn the_indicator_counts_windows_after_a_removal() {
+        let mut slots = slots(&["Work", "Browsing", "Files"]);
+        slots.remove_workspace(1).unwrap();
+        assert_eq!(indicator(&slots, 0), ["Work :2", "Files :1"]);
+    }
+
+    #[test]
+    fn added_workspaces_go_last() {
+        let mut slots = slots(&["Work", "Browsing"]);
+        assert_eq!(slots.add_workspace(Workspace::new("Music", vec![])), 2);
+    }
+
+    #[test]
+    fn removing_the_first_hands_its_windows_to_the_next() {
+        let mut slots = slots(&["Work", "Files", "Music"]);
+        slots.set_active_workspace(1, 2).unwrap();
+
+        assert_eq!(slots.remove_workspace(0), Some(0));
+        assert_eq!(
+            names(&slots),
+            vec![
+                ("Files".to_string(), vec![2, 1]),
+                ("Music".to_string(), vec![3]),
+            ]
+        );
+        assert_eq!(slots.active_workspace_per_monitor, vec![0, 1]);
+    }
+
+    #[test]
+    fn swapping_reindexes_the_active_workspace() {
+        let mut slots = slots(&["Files", "Music"]);
+        slots.set_active_workspace(1, 1).unwrap();
+
+        slots.swap_workspaces(0, 1).unwrap();
+        assert_eq!(names(&slots)[0].0, "Music");
+        assert_eq!(slots.active_workspace_per_monitor, vec![1, 0]);
+    }
+
+    #[test]
+    fn swapping_out_of_range_is_none() {
+        let mut slots = slots(&["Files", "Music"]);
+        assert!(slots.swap_workspaces(1, 2).is_none());
+        assert_eq!(names(&slots)[1].0, "Music");
+    }
+
+    #[test]
+    fn the_last_workspace_cant_be_removed() {
+        let mut slots = slots(&["Files"]);
+        assert_eq!(slots.remove_workspace(0), None);
+        assert_eq!(names(&slots), vec![("Files".to_string(), vec![1])]);
+    }
+
+    #[test]
+    fn syncing_keeps_windows_on_their_monitor() {
+        let slots = owned_slots();
+        // `Browsing` is gone, its window stays on the second monitor
+        assert_eq!(
+            names(&slots),
+            vec![
+                ("Work".to_string(), vec![1]),
+                ("Files".to_string(), vec![]),
+                ("Chat".to_string(), vec![2]),
+            ]
+        );
+        assert_eq!(slots.active_workspace_per_monitor, vec![0, 2]);
+    }
+
+    #[test]
+    fn workspaces_on_lists_the_monitors_own() {
+        let slots = owned_slots();
+        assert_eq!(slots.workspaces_on(0), [0, 1]);
+        assert_eq!(slots.workspaces_on(1), [2]);
+    }
+
+    #[test]
+    fn a_monitor_cant_show_another_monitors_workspace() {
+        let mut slots = owned_slots();
+        assert!(slots.set_active_workspace(1, 1).is_none());
+        assert_eq!(slots.active_workspace_per_monitor, vec![0, 2]);
+    }
+
+    #[test]
+    fn names_only_clash_on_the_same_monitor() {
+        let slots = owned_slots();
+        assert!(slots.check_workspace_name("Work", 1).is_ok());
+        assert!(slots.check_workspace_name("Work", 0).is_err());
+    }
+
+    #[test]
+    fn the_last_workspace_of_a_monitor_stays() {
+        let mut slots = owned_slots();
+        assert_eq!(slots.remove_workspace(2), None);
+        assert_eq!(slots.remove_workspace(0), Some(0));
+    }
+
+    #[test]
+    fn the_indicator_shows_the_monitors_own_workspaces() {
+        let slots = owned_slots();
+        assert_eq!(indicator(&slots, 1), ["Chat :1"]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::overlay::app_info::{AppPosition, AppSize};
 use crate::overlay::monitor_info::StatusbarMonitorInfo;
+use crate::overlay::scratchpad;
 use crate::overlay::win_event::WinEvent;
 use crate::overlay::{app_info::AppInfo, app_window::AppWindow};
 use anyhow::Context;
@@ -19,9 +20,9 @@ use windows::{
         Foundation::*,
         Graphics::{Dwm::*, Gdi::*},
         System::Threading::*,
-        UI::{Input::KeyboardAndMouse::SetFocus, WindowsAndMessaging::*},
+        UI::{Input::KeyboardAndMouse::SetFocus, Shell::ShellExecuteW, WindowsAndMessaging::*},
     },
-    core::{BOOL, PWSTR},
+    core::{BOOL, HSTRING, PCWSTR, PWSTR},
 };
 
 pub static APP_WINDOW_PADDING: i32 = 0;
@@ -154,6 +155,9 @@ pub(crate) fn get_dwm_rect(hwnd: HWND, thickness: i32) -> Rect {
 pub fn is_maximized(hwnd: isize) -> bool {
     unsafe { IsZoomed(HWND(hwnd as *mut c_void)).as_bool() }
 }
+pub fn is_minimized(hwnd: isize) -> bool {
+    unsafe { IsIconic(HWND(hwnd as *mut c_void)).as_bool() }
+}
 pub fn get_rect_padding(hwnd: isize) -> (i32, i32) {
     let dwm_rect = get_dwm_rect(crate::hwnd!(hwnd), 0);
     let rect = get_rect(crate::hwnd!(hwnd));
@@ -284,6 +288,26 @@ pub fn close_app(hwnd: HWND) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// through the shell, so names from App Paths like `wezterm-gui.exe` work too
+pub fn launch(command: &str) -> anyhow::Result<()> {
+    let (program, args) = scratchpad::split_command(command);
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            &HSTRING::from("open"),
+            &HSTRING::from(program),
+            &HSTRING::from(args),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // anything up to 32 is an error code
+    if result.0 as usize <= 32 {
+        anyhow::bail!("Failed to launch `{command}`: {}", result.0 as usize);
+    }
+    Ok(())
+}
+
 pub(crate) fn get_app_position(hwnd: HWND) -> AppPosition {
     get_rect(hwnd).1
 }
@@ -401,6 +425,34 @@ pub fn toggle_top_most(hwnd: HWND, parent_hwnd: HWND) -> bool {
     !top_most
 }
 
+/// id of the calling thread, for `quit_thread`
+pub fn current_thread_id() -> u32 {
+    unsafe { GetCurrentThreadId() }
+}
+
+/// ends the message loop of `thread_id`, hooks set on that thread go with it
+pub fn quit_thread(thread_id: u32) {
+    _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+}
+
+pub fn list_windows() -> Vec<isize> {
+    extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let hwnds = unsafe { &mut *(lparam.0 as *mut Vec<isize>) };
+        if unsafe { IsWindowVisible(hwnd) }.as_bool() {
+            hwnds.push(hwnd.0 as isize);
+        }
+        TRUE
+    }
+    let mut hwnds: Vec<isize> = Vec::new();
+    _ = unsafe {
+        EnumWindows(
+            Some(collect),
+            LPARAM(&mut hwnds as *mut Vec<isize> as isize),
+        )
+    };
+    hwnds
+}
+
 pub fn app_exist(hwnd: HWND) {
     let exits = unsafe { IsWindow(Some(hwnd)).as_bool() };
 }
@@ -1,5 +1,14 @@
+use std::collections::BTreeMap;
+
+use crate::overlay::{
+    config::LayoutKind,
+    layout::{
+        bsp::BspTree, master_stack::MasterStack, monocle::Monocle, scrolling::ScrollingStrip,
+    },
+};
+
 pub type Hwnd = isize;
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct HwndItem {
     pub hwnd: Hwnd,
     pub app_name: String,
@@ -20,7 +29,32 @@ impl HwndItem {
 pub struct Workspace {
     pub text: String,
     pub active: bool,
+    // the only monitor showing it when monitors have their own workspaces
+    pub monitor: Option<usize>,
     pub hwnds: Vec<HwndItem>,
+    pub layout: LayoutKind,
+    // one tree per monitor
+    pub bsp: BTreeMap<usize, BspTree>,
+    pub master_stack: MasterStack,
+    // one strip per monitor
+    pub columns: BTreeMap<usize, ScrollingStrip>,
+    // one stack per monitor
+    pub monocle: BTreeMap<usize, Monocle>,
+    // most recently focused first
+    pub focus_history: Vec<Hwnd>,
+}
+
+/// what `get_all_workspaces` reports about one workspace
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct WorkspaceInfo {
+    pub index: usize,
+    pub name: String,
+    pub layout: LayoutKind,
+    // the monitor it belongs to, `None` when all monitors share it
+    pub monitor: Option<usize>,
+    // monitors currently showing it
+    pub active_on: Vec<usize>,
+    pub windows: Vec<HwndItem>,
 }
 
 impl Workspace {
@@ -28,7 +62,51 @@ impl Workspace {
         Self {
             text: ws.to_string(),
             active: true,
+            monitor: None,
             hwnds,
+            layout: LayoutKind::default(),
+            bsp: BTreeMap::new(),
+            master_stack: MasterStack::default(),
+            columns: BTreeMap::new(),
+            monocle: BTreeMap::new(),
+            focus_history: vec![],
+        }
+    }
+    /// `hwnd` got focus, windows that left the workspace are forgotten
+    pub fn remember_focus(&mut self, hwnd: Hwnd) {
+        let hwnds = &self.hwnds;
+        self.focus_history
+            .retain(|h| *h != hwnd && hwnds.iter().any(|item| item.hwnd == *h));
+        self.focus_history.insert(0, hwnd);
+    }
+    /// the window on `monitor` that had focus last and is still here
+    pub fn last_focused(&self, monitor: usize) -> Option<Hwnd> {
+        self.focus_history.iter().copied().find(|hwnd| {
+            self.hwnds
+                .iter()
+                .any(|h| h.hwnd == *hwnd && h.monitor == monitor)
+        })
+    }
+    /// `monitor` may show it
+    pub fn belongs_to(&self, monitor: usize) -> bool {
+        self.monitor.is_none_or(|m| m == monitor)
+    }
+    pub fn hwnds_on_monitor(&self, monitor: usize) -> Vec<Hwnd> {
+        self.hwnds
+            .iter()
+            .filter(|h| h.monitor == monitor)
+            .map(|h| h.hwnd)
+            .collect()
+    }
+    /// "n/m" position of the shown window when this is a monocle workspace
+    pub fn monocle_position(&self, monitor: usize) -> Option<(usize, usize)> {
+        if self.layout != LayoutKind::Monocle {
+            return None;
         }
+        self.monocle
+            .get(&monitor)
+            .cloned()
+            .unwrap_or_default()
+            .position(&self.hwnds_on_monitor(monitor))
     }
 }